@@ -1,115 +1,292 @@
-use crate::data::{DataState, Metadata};
+use crate::data::{
+    AdvisoryDb, CargoMetadata, DataState, Declaration, DependencyKind, Graph, LICENSE_FILTER,
+    LoadEvent, Loader, ManifestEdit, MetadataOptions, NAME_FILTER, Platform, Policy, RegistryIndex,
+    Removal, SizeMode, WORKSPACE_ID,
+};
 use crate::error;
 use crate::ui::{DisplayMode, OrderBy, Screen};
 
-use open;
 use ratatui::crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
-use serde_json::Value;
-use std::collections::HashMap;
-use crate::error::Errors::CargoTomlNotFound;
 
 pub struct App {
     state: DataState,
     screen: Screen,
+    path: String,
+    /// The running background load, if any.
+    loader: Option<Loader>,
+    /// Where newer releases are looked up.
+    index: RegistryIndex,
 }
 
 impl App {
-    fn default() -> App {
-        App {
+    /// Starts loading the project at `path`, checking licenses against the policy in `license_policy` or the project
+    /// and packages against the advisory database at `advisory_db` or its usual place.
+    /// Newer releases are looked up in the registry index at `index_path`, or the one cargo caches.
+    pub fn new(
+        path: &str,
+        options: MetadataOptions,
+        license_policy: Option<&str>,
+        advisory_db: Option<&str>,
+        index_path: Option<&str>,
+    ) -> (Self, Vec<error::Errors>) {
+        let mut res = Self {
             state: DataState::default(),
             screen: Screen::default(),
+            path: path.to_string(),
+            loader: None,
+            index: RegistryIndex::default(),
+        };
+        let mut errors = Vec::new();
+        match RegistryIndex::locate(index_path) {
+            Ok(index) => res.index = index,
+            Err(e) => errors.push(e),
+        }
+        match Policy::load(path, license_policy) {
+            Ok(policy) => res.state.license_policy = policy,
+            Err(e) => errors.push(e),
         }
+        match AdvisoryDb::load(advisory_db) {
+            Ok(db) => res.state.advisory_db = db,
+            Err(e) => errors.push(e),
+        }
+        errors.extend(res.load(options).err());
+        (res, errors)
     }
-    pub fn new<F: FnMut(&str) -> error::Result<()>>(
-        path: &str,
-        mut loading_screen_callback: F,
-    ) -> (Self, Vec<error::Errors>) {
-        let mut errors = Vec::new();
-        errors.extend(loading_screen_callback("Loading...").err());
-        // if Cargo.toml is not found, show error
-        if !std::path::Path::new(path).join("Cargo.toml").exists() {
-            errors.push(CargoTomlNotFound);
-            return (App::default(), errors)
+
+    /// Starts resolving the graph with the given options in the background.
+    ///
+    /// Sizes already measured are kept, the results are picked up by [`App::tick`].
+    fn load(&mut self, options: MetadataOptions) -> error::Result<()> {
+        if let Some(source) = options.fixed_source()
+            && !self.state.graph.is_empty()
+        {
+            return Err(error::Errors::FixedGraph(source));
         }
-        
-        let output = std::process::Command::new("cargo")
-            .arg("metadata")
-            .arg("--format-version")
-            .arg("1")
-            .current_dir(path)
-            .output();
-        let output = match output {
-            Ok(output) => output,
-            Err(_err) => {
-                errors.push(error::Errors::RunCargoMetadata);
-                return (Self::default(), errors);
-            }
-        };
+        let known = self
+            .state
+            .graph
+            .packages()
+            .filter(|(_index, metadata)| metadata.size.is_known())
+            .map(|(_index, metadata)| metadata.id.clone())
+            .collect();
+        self.loader = Some(Loader::spawn(
+            self.path.clone(),
+            options,
+            known,
+            self.index.clone(),
+        ));
+        self.state.loading = self.loader.as_ref().map(|loader| loader.progress);
+        Ok(())
+    }
 
-        let metadata: Value = match serde_json::from_slice(&output.stdout) {
-            Ok(metadata) => metadata,
-            Err(_err) => {
-                errors.push(error::Errors::ParseMetadata);
-                Value::Null
-            }
+    /// Picks up whatever the background load produced since the last tick.
+    pub fn tick(&mut self) -> error::Result<()> {
+        let Some(loader) = self.loader.as_mut() else {
+            return Ok(());
         };
-        let mut deps_map = HashMap::new();
-
-        if let Some(Value::Array(nodes)) = metadata
-            .get("resolve")
-            .and_then(|resolve| resolve.get("nodes")) {
-            if let Some(Value::Array(packages)) = metadata.get("packages") {
-                for package in packages {
-                    if let Some(name) = package.get("name") {
-                        if let Some(version) = package.get("version") {
-                            let id = get_string_from(package, "id");
-                            if deps_map.get(id.as_str()).is_some() {
-                                continue;
-                            }
+        let events = loader.events();
+        let options = loader.options.clone();
+        let finished = loader.is_finished();
+        self.state.loading = Some(loader.progress);
 
-                            deps_map.insert(
-                                id.clone(),
-                                Metadata {
-                                    size: get_size_by_manifest_path(get_string_from(package, "manifest_path")).unwrap_or(0),
-                                    name: trim_value(name),
-                                    version: trim_value(version),
-                                    license: get_string_from(package, "license"),
-                                    documentation: get_string_from(package, "documentation"),
-                                    description: get_string_from(package, "description"),
-                                    dependencies: nodes
-                                        .iter()
-                                        .find(|node| get_string_from(node, "id") == id)
-                                        .map(|node| get_vec_from(node, "dependencies"))
-                                        .unwrap_or_default(),
-                                },
-                            );
-                        }
+        let mut resized = false;
+        let mut recounted = false;
+        let mut warning = None;
+        for event in events {
+            match event {
+                LoadEvent::Metadata(metadata, _total) => {
+                    self.replace_graph(*metadata, options.clone())
+                }
+                LoadEvent::Artifacts(artifacts) => {
+                    for metadata in self.state.graph.packages_mut() {
+                        metadata.artifacts = artifacts
+                            .iter()
+                            .filter_map(|(profile, sizes)| {
+                                Some((profile.clone(), *sizes.get(&metadata.id)?))
+                            })
+                            .collect();
+                    }
+                    self.state.profiles = artifacts.into_keys().collect();
+                    if !matches!(&self.state.size_mode, SizeMode::Compiled(profile) if self.state.profiles.contains(profile))
+                    {
+                        self.state.size_mode = SizeMode::Source;
+                    }
+                    resized = true;
+                }
+                LoadEvent::Timings(timings) => {
+                    for metadata in self.state.graph.packages_mut() {
+                        metadata.timing = timings.get(&metadata.id).copied();
+                    }
+                    resized = true;
+                }
+                LoadEvent::Updates(updates) => {
+                    for metadata in self.state.graph.packages_mut() {
+                        metadata.updates = updates.get(&metadata.id).cloned();
+                    }
+                }
+                LoadEvent::Code(id, stats) => {
+                    if let Some(index) = self.state.graph.index_of(&id) {
+                        self.state.graph[index].code = Some(stats);
+                        recounted = true;
                     }
                 }
+                LoadEvent::Size(id, size) => {
+                    if let Some(index) = self.state.graph.index_of(&id) {
+                        self.state.graph[index].size = size;
+                        resized = true;
+                    }
+                }
+                LoadEvent::Fallback(e) => {
+                    warning = Some(error::Errors::LockfileFallback(Box::new(e)))
+                }
+                LoadEvent::Failed(e) => {
+                    self.loader = None;
+                    self.state.loading = None;
+                    return Err(e);
+                }
+            }
+        }
+        if finished {
+            self.loader = None;
+            self.state.loading = None;
+        }
+        if recounted || resized {
+            self.state.count_cumulative();
+            // sorting by files, lines or unsafe uses depends on the counts as much as on the sizes
+            self.state.refresh();
+        }
+        if resized {
+            if self.screen.mode == DisplayMode::Workspace {
+                self.state.workspace = self.state.workspace_summary();
+            }
+            if self.screen.mode == DisplayMode::Duplicates {
+                self.state.duplicates = self.state.find_duplicates();
             }
         }
+        // the sizes of packages coming back with an undo are only known once measured again
+        if finished {
+            let footprint = self.state.footprint();
+            if let Some(edit) = self
+                .state
+                .last_edit
+                .as_mut()
+                .filter(|edit| edit.after.is_none())
+            {
+                edit.after = Some(footprint);
+            }
+        }
+        warning.map_or(Ok(()), Err)
+    }
 
+    /// Stops the background load, sizes not measured yet stay unknown.
+    fn cancel_loading(&mut self) {
+        self.loader = None;
+        self.state.loading = None;
+    }
 
+    /// Replaces the dependency graph with freshly resolved metadata.
+    ///
+    /// The navigation path and the selected row are kept as far as they still exist.
+    fn replace_graph(&mut self, metadata: CargoMetadata, options: MetadataOptions) {
+        let graph = Graph::build(&metadata, &self.state.graph);
 
-        // get root from workspace_default_members
-        let root_id = metadata
-            .get("workspace_default_members")
-            .and_then(|members| members.get(0))
-            .and_then(|member| member.as_str())
-            .unwrap_or_default()
-            .to_string();
-        
-        let mut res = Self {
-            state: DataState::default(),
-            screen: Screen::default(),
+        // a single default member is the root, several of them stand for the whole workspace
+        let root_id = match metadata.workspace_default_members.as_slice() {
+            [member] => member.as_str(),
+            _ if graph.index_of(WORKSPACE_ID).is_some() => WORKSPACE_ID,
+            _ => metadata
+                .workspace_members
+                .first()
+                .map_or("", String::as_str),
         };
-        res.state.deps_map = deps_map;
-        let root = res.state.get_metadata(root_id);
-        res.state.selected_package = vec![root.clone()];
-        res.state.level1_deps = res.state.get_deps(root);
-        res.select_first_row();
-        (res, errors)
+
+        // indices change with the graph, so the path is carried over by package id
+        let mut path: Vec<usize> = self
+            .state
+            .selected_package
+            .iter()
+            .map_while(|index| graph.index_of(&self.state.graph[*index].id))
+            .collect();
+        if path.is_empty() {
+            path.extend(graph.index_of(root_id));
+        }
+        let selected = self
+            .state
+            .get_selected_dep()
+            .and_then(|dep| graph.index_of(&dep.id));
+        self.state.forward_path = self
+            .state
+            .forward_path
+            .iter()
+            .map_while(|index| graph.index_of(&self.state.graph[*index].id))
+            .collect();
+
+        self.state.set_graph(graph);
+        self.state.workspace_members = metadata.workspace_members;
+        self.state.check_licenses();
+        self.state.check_advisories();
+        self.state.options = options;
+        self.state.level2_deps = Vec::new();
+        self.state.restore_path(&path, selected);
+        self.screen.viewport_start = 0;
+    }
+
+    /// Removes the selected dependency from the manifest of the workspace member listing it, not written yet.
+    ///
+    /// Only the kinds shown in the table are removed.
+    fn plan_removal(&self) -> error::Result<Option<Removal>> {
+        if let Some(source) = self.state.options.fixed_source() {
+            return Err(error::Errors::FixedGraph(source));
+        }
+        let (Some(parent), Some(selected)) = (
+            self.state.selected_package.last().copied(),
+            self.state.get_selected_dep(),
+        ) else {
+            return Ok(None);
+        };
+        let member = &self.state.graph[parent];
+        let declarations: Vec<Declaration> = self
+            .state
+            .graph
+            .dependencies(parent)
+            .iter()
+            .filter(|edge| edge.index == selected.index)
+            .flat_map(|edge| edge.kinds.iter())
+            .filter(|edge_kind| self.state.shown_kinds.contains(&edge_kind.kind))
+            .map(|edge_kind| Declaration {
+                kind: edge_kind.kind,
+                platform: edge_kind
+                    .platform
+                    .map(|platform| self.state.graph.platform(platform).to_string()),
+            })
+            .collect();
+        if self.state.is_reverse
+            || !self.state.workspace_members.contains(&member.id)
+            || declarations.is_empty()
+        {
+            return Err(error::Errors::NotRemovable(selected.name.clone()));
+        }
+        Removal::plan(
+            &member.manifest_path,
+            &member.name,
+            &selected.name,
+            &declarations,
+        )
+        .map(Some)
+    }
+
+    /// Writes the manifest of the last edit back as it was and resolves the graph again.
+    fn undo_edit(&mut self) -> error::Result<()> {
+        let footprint = self.state.footprint();
+        let Some(edit) = self.state.last_edit.as_mut().filter(|edit| !edit.undone) else {
+            return Err(error::Errors::NothingToUndo);
+        };
+        edit.removal.revert()?;
+        edit.undone = true;
+        edit.before = footprint;
+        edit.after = None;
+        self.load(self.state.options.clone())
     }
 
     fn select_first_row(&mut self) {
@@ -118,80 +295,80 @@ impl App {
     }
 
     pub fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        if self.state.graph.is_empty()
+            && let Some(progress) = self.state.loading
+        {
+            self.screen.render_loading(area, buf, progress);
+            return;
+        }
         self.screen.display(area, buf, &self.state);
     }
 
-    pub fn update(&mut self, key: KeyEvent) {
+    pub fn update(&mut self, key: KeyEvent) -> error::Result<()> {
         match self.screen.mode {
             DisplayMode::View => {
                 match key.code {
-                    KeyCode::Char('a' | 'A') => {
-                        if self.state.is_direct {
-                            self.state.is_direct = false; 
-                            self.state.switch_mode();
-                        }
+                    KeyCode::Char('a' | 'A') if self.state.is_direct => {
+                        self.state.is_direct = false;
+                        self.state.switch_mode();
                     }
-                    KeyCode::Char('d' | 'D') => {
-                        if !self.state.is_direct {
-                            self.state.is_direct = true;    
-                            self.state.switch_mode();
-                        }
+                    KeyCode::Char('d' | 'D') if !self.state.is_direct => {
+                        self.state.is_direct = true;
+                        self.state.switch_mode();
                     }
+                    KeyCode::Char('1') => self.state.toggle_kind(DependencyKind::Normal),
+                    KeyCode::Char('2') => self.state.toggle_kind(DependencyKind::Development),
+                    KeyCode::Char('3') => self.state.toggle_kind(DependencyKind::Build),
                     KeyCode::Char('c' | 'C') => {
                         self.screen.clear_filter(&mut self.state);
                         self.screen.mode = DisplayMode::View;
                     }
-                    KeyCode::Left => {
-                        if self.state.selected_package.len() > 1 {
-                            self.screen.viewport_start = 0;
-                            self.state.selected_package.pop();
-                            self.state.level2_deps = Vec::new();
-                            if let Some(last_dep) = self.state.selected_package.last() {
-                                self.state.level1_deps = self.state.get_deps(last_dep.clone())
-                            } else {
-                                self.state.level1_deps = Vec::new();
-                            }
-                            self.select_first_row();
+                    KeyCode::Left if self.state.selected_package.len() > 1 => {
+                        self.screen.viewport_start = 0;
+                        self.state.selected_package.pop();
+                        self.state.level2_deps = Vec::new();
+                        if let Some(last_dep) = self.state.selected_package.last() {
+                            self.state.level1_deps = self.state.get_deps(*last_dep)
+                        } else {
+                            self.state.level1_deps = Vec::new();
                         }
+                        self.select_first_row();
                     }
-                    KeyCode::Right | KeyCode::Char('l' | 'L') => {
-                        if self.state.level2_deps.len() > 0 {
-                            self.screen.viewport_start = 0;
-                            self.state.selected_package.push(
-                                self.state.get_selected_dep(),
-                            );
-                            self.state.level1_deps = self.state.level2_deps.clone();
-                            self.state.level2_deps = Vec::new();
-                            self.select_first_row();
+                    KeyCode::Right | KeyCode::Char('l' | 'L')
+                        if !self.state.level2_deps.is_empty() =>
+                    {
+                        self.screen.viewport_start = 0;
+                        if let Some(selected) = self.state.get_selected_dep() {
+                            self.state.selected_package.push(selected.index);
                         }
+                        self.state.level1_deps = self.state.level2_deps.clone();
+                        self.state.level2_deps = Vec::new();
+                        self.select_first_row();
                     }
-                    KeyCode::Up | KeyCode::Char('K' | 'k') => {
-                        if self.state.selected_index > 0 {
-                            self.state.selected_index -= 1;
-                            self.state.get_level2_dep();
-                        }
+                    KeyCode::Up | KeyCode::Char('K' | 'k') if self.state.selected_index > 0 => {
+                        self.state.selected_index -= 1;
+                        self.state.get_level2_dep();
                     }
-                    KeyCode::Down | KeyCode::Char('J' | 'j') => {
-                        if self.state.selected_index < self.state.get_filter_deps().len() - 1 {
-                            self.state.selected_index += 1;
-                            self.state.get_level2_dep();
-                        }
+                    KeyCode::Down | KeyCode::Char('J' | 'j')
+                        if self.state.selected_index + 1 < self.state.get_filter_deps().len() =>
+                    {
+                        self.state.selected_index += 1;
+                        self.state.get_level2_dep();
                     }
                     KeyCode::Enter => {
-                        if let Some(metadata) =
-                            self.state.level1_deps.get(self.state.selected_index)
+                        if let Some(metadata) = self.state.get_selected_dep()
+                            && !metadata.documentation.is_empty()
                         {
-                            if !metadata.documentation.is_empty() {
-                                // Open documentation URL
-                                if let Err(e) = open::that(&metadata.documentation) {
-                                    eprintln!("Failed to open documentation: {}", e);
-                                }
+                            // Open documentation URL
+                            if let Err(e) = open::that(&metadata.documentation) {
+                                eprintln!("Failed to open documentation: {}", e);
                             }
                         }
                     }
                     KeyCode::Char('f' | 'F' | '/') => {
                         self.screen.mode = DisplayMode::Filter;
                     }
+                    KeyCode::Esc if self.loader.is_some() => self.cancel_loading(),
                     KeyCode::Char('v' | 'V') | KeyCode::Esc => {
                         self.screen.mode = DisplayMode::View;
                     }
@@ -201,6 +378,80 @@ impl App {
                     KeyCode::Char('s' | 'S') => {
                         self.screen.mode = DisplayMode::Sort;
                     }
+                    KeyCode::Char('t' | 'T') => {
+                        if self.state.platforms.is_empty() {
+                            self.state.platforms = Platform::choices();
+                        }
+                        self.screen.platform_index = self
+                            .state
+                            .platforms
+                            .iter()
+                            .position(|platform| *platform == self.state.options.platform)
+                            .unwrap_or_default();
+                        self.screen.mode = DisplayMode::Platform;
+                    }
+                    KeyCode::Char('e' | 'E') => {
+                        self.screen.feature_index = 0;
+                        self.screen.mode = DisplayMode::Features;
+                    }
+                    KeyCode::Char('b' | 'B') => {
+                        self.screen.viewport_start = 0;
+                        self.state.next_size_mode();
+                    }
+                    KeyCode::Char('w' | 'W') => {
+                        self.state.workspace = self.state.workspace_summary();
+                        self.screen.workspace_index = self
+                            .state
+                            .workspace
+                            .iter()
+                            .position(|member| {
+                                self.state.selected_package.first() == Some(&member.index)
+                            })
+                            .unwrap_or_default();
+                        self.screen.mode = DisplayMode::Workspace;
+                    }
+                    KeyCode::Char('x' | 'X') => self.state.next_share(),
+                    KeyCode::Char('r' | 'R') => {
+                        self.screen.reset_filter_area();
+                        self.screen.viewport_start = 0;
+                        self.state.toggle_reverse();
+                    }
+                    KeyCode::Char('p' | 'P') => {
+                        if let Some(selected) = self.state.get_selected_dep().map(|dep| dep.index) {
+                            self.state.paths = self.state.find_paths(selected);
+                            self.screen.paths_index = 0;
+                            self.screen.path_step = 0;
+                            self.screen.mode = DisplayMode::Paths;
+                        }
+                    }
+                    KeyCode::Char('u' | 'U') => {
+                        self.state.duplicates = self.state.find_duplicates();
+                        self.screen.duplicates_index = 0;
+                        self.screen.mode = DisplayMode::Duplicates;
+                    }
+                    KeyCode::Char('n' | 'N') => {
+                        self.state.findings = self.state.find_advisories();
+                        self.screen.advisories_index = 0;
+                        self.screen.mode = DisplayMode::Advisories;
+                    }
+                    KeyCode::Char('g' | 'G') => {
+                        self.state.license_groups = self.state.license_summary();
+                        self.screen.licenses_index = 0;
+                        self.screen.mode = DisplayMode::Licenses;
+                    }
+                    KeyCode::Char('i' | 'I') => {
+                        self.state.flagged = self.state.find_license_issues();
+                        self.screen.license_issues_index = 0;
+                        self.screen.mode = DisplayMode::LicenseIssues;
+                    }
+                    KeyCode::Delete => {
+                        if let Some(removal) = self.plan_removal()? {
+                            self.state.removal = Some(removal);
+                            self.screen.removal_scroll = 0;
+                            self.screen.mode = DisplayMode::RemoveDependency;
+                        }
+                    }
+                    KeyCode::Char('z' | 'Z') => return self.undo_edit(),
                     _ => {}
                 }
             }
@@ -242,6 +493,38 @@ impl App {
                     self.state.order_by(OrderBy::Version);
                     self.screen.mode = DisplayMode::View;
                 }
+                KeyCode::Char('f' | 'F') => {
+                    self.state.order_by(OrderBy::Files);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('l' | 'L') => {
+                    self.state.order_by(OrderBy::Lines);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('u' | 'U') => {
+                    self.state.order_by(OrderBy::Unsafe);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('b' | 'B') => {
+                    self.state.order_by(OrderBy::BuildTime);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('e' | 'E') => {
+                    self.state.order_by(OrderBy::ExclusiveSize);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('x' | 'X') => {
+                    self.state.order_by(OrderBy::ExclusiveCount);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('c' | 'C') => {
+                    self.state.order_by(OrderBy::CumulativeSize);
+                    self.screen.mode = DisplayMode::View;
+                }
+                KeyCode::Char('t' | 'T') => {
+                    self.state.order_by(OrderBy::CumulativeCount);
+                    self.screen.mode = DisplayMode::View;
+                }
                 KeyCode::Char('r' | 'R') => {
                     self.state.sorting(!self.state.sorting_asc);
                     self.screen.mode = DisplayMode::View;
@@ -251,45 +534,257 @@ impl App {
                 }
                 _ => {}
             },
+            DisplayMode::Platform => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.platform_index = self.screen.platform_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.platform_index + 1 < self.state.platforms.len() =>
+                {
+                    self.screen.platform_index += 1;
+                }
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(platform) = self.state.platforms.get(self.screen.platform_index) {
+                        let options = MetadataOptions {
+                            platform: platform.clone(),
+                            ..self.state.options.clone()
+                        };
+                        return self.load(options);
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Features => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.feature_index = self.screen.feature_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.feature_index + 1 < self.state.feature_toggles().len() =>
+                {
+                    self.screen.feature_index += 1;
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    if let Some(toggle) =
+                        self.state.feature_toggles().get(self.screen.feature_index)
+                    {
+                        let mut options = self.state.options.clone();
+                        options.toggle(toggle);
+                        options.manifest_path =
+                            self.state.root().map(|root| root.manifest_path.clone());
+                        return self.load(options);
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Workspace => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.workspace_index = self.screen.workspace_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.workspace_index + 1 < self.state.workspace.len() =>
+                {
+                    self.screen.workspace_index += 1;
+                }
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(member) = self.state.workspace.get(self.screen.workspace_index) {
+                        let index = member.index;
+                        self.screen.clear_filter(&mut self.state);
+                        self.screen.viewport_start = 0;
+                        self.state.select_root(index);
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Paths => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.paths_index = self.screen.paths_index.saturating_sub(1);
+                    self.screen.path_step = 0;
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.paths_index + 1 < self.state.paths.len() =>
+                {
+                    self.screen.paths_index += 1;
+                    self.screen.path_step = 0;
+                }
+                KeyCode::Left | KeyCode::Char('h' | 'H') => {
+                    self.screen.path_step = self.screen.path_step.saturating_sub(1);
+                }
+                KeyCode::Right | KeyCode::Char('l' | 'L')
+                    if self
+                        .state
+                        .paths
+                        .get(self.screen.paths_index)
+                        .is_some_and(|path| self.screen.path_step + 1 < path.len()) =>
+                {
+                    self.screen.path_step += 1;
+                }
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(path) = self.state.paths.get(self.screen.paths_index) {
+                        let path: Vec<usize> = path.iter().map(|step| step.index).collect();
+                        self.screen.reset_filter_area();
+                        self.state.jump_to(&path, self.screen.path_step);
+                        self.screen.viewport_start = self.state.selected_index;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Advisories => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.advisories_index = self.screen.advisories_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.advisories_index + 1 < self.state.findings.len() =>
+                {
+                    self.screen.advisories_index += 1;
+                }
+                // the package, selected below its parent on the path from the root
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(finding) = self.state.findings.get(self.screen.advisories_index)
+                        && !finding.path.is_empty()
+                    {
+                        let path = finding.path.clone();
+                        self.screen.reset_filter_area();
+                        self.state.jump_to(&path, path.len() - 1);
+                        self.screen.viewport_start = self.state.selected_index;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Licenses => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.licenses_index = self.screen.licenses_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.licenses_index + 1 < self.state.license_groups.len() =>
+                {
+                    self.screen.licenses_index += 1;
+                }
+                // the crates of the license, in the view the summary was opened from
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(group) = self.state.license_groups.get(self.screen.licenses_index) {
+                        let filter = format!("{}{}", LICENSE_FILTER, group.license);
+                        self.screen.viewport_start = 0;
+                        self.screen.set_filter(&mut self.state, &filter);
+                        self.state.get_level2_dep();
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::LicenseIssues => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.license_issues_index =
+                        self.screen.license_issues_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.license_issues_index + 1 < self.state.flagged.len() =>
+                {
+                    self.screen.license_issues_index += 1;
+                }
+                // the crate, listed below the root
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(index) = self
+                        .state
+                        .flagged
+                        .get(self.screen.license_issues_index)
+                        .copied()
+                        && let Some(root) = self.state.selected_package.first().copied()
+                    {
+                        let filter = format!("{}{}", NAME_FILTER, self.state.graph[index].name);
+                        self.state.is_direct = false;
+                        self.screen.viewport_start = 0;
+                        self.state.select_root(root);
+                        self.screen.set_filter(&mut self.state, &filter);
+                        self.state.get_level2_dep();
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::RemoveDependency => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.removal_scroll = self.screen.removal_scroll.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.state.removal.as_ref().is_some_and(|removal| {
+                        (self.screen.removal_scroll as usize) + 1 < removal.diff.len()
+                    }) =>
+                {
+                    self.screen.removal_scroll += 1;
+                }
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(removal) = self.state.removal.take() {
+                        removal.apply()?;
+                        self.state.last_edit = Some(ManifestEdit {
+                            removal,
+                            before: self.state.footprint(),
+                            after: None,
+                            undone: false,
+                        });
+                        return self.load(self.state.options.clone());
+                    }
+                }
+                KeyCode::Esc => {
+                    self.state.removal = None;
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
+            DisplayMode::Duplicates => match key.code {
+                KeyCode::Up | KeyCode::Char('k' | 'K') => {
+                    self.screen.duplicates_index = self.screen.duplicates_index.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j' | 'J')
+                    if self.screen.duplicates_index + 1 < self.state.duplicates.len() =>
+                {
+                    self.screen.duplicates_index += 1;
+                }
+                // every version of the crate, listed below the root
+                KeyCode::Enter => {
+                    self.screen.mode = DisplayMode::View;
+                    if let Some(duplicate) = self.state.duplicates.get(self.screen.duplicates_index)
+                        && let Some(root) = self.state.selected_package.first().copied()
+                    {
+                        let filter = format!("{}{}", NAME_FILTER, duplicate.name);
+                        self.state.is_direct = false;
+                        self.screen.viewport_start = 0;
+                        self.state.select_root(root);
+                        self.screen.set_filter(&mut self.state, &filter);
+                        self.state.get_level2_dep();
+                    }
+                }
+                KeyCode::Esc => {
+                    self.screen.mode = DisplayMode::View;
+                }
+                _ => {}
+            },
         };
+        Ok(())
     }
 }
-
-fn get_vec_from(content: &Value, key: &str) -> Vec<String> {
-    content
-        .get(key)
-        .and_then(|v| match v {
-            Value::Array(arr) => {
-                let strings: Vec<String> = arr
-                    .iter()
-                    .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                    .collect();
-                Some(strings)
-            }
-            _ => None,
-        })
-        .unwrap_or(Vec::new())
-}
-
-fn get_string_from(content: &Value, key: &str) -> String {
-    content
-        .get(key)
-        .map(|v| v.as_str().unwrap_or_default())
-        .unwrap_or_default()
-        .to_string()
-}
-
-fn trim_value(value: &Value) -> String {
-    value.to_string().trim()
-        .trim_matches(|c: char| c.is_whitespace() || c == '"').to_string()
-}
-
-pub fn get_size_by_manifest_path(path: String) -> Result<u64, std::io::Error> {
-    // "/Users/yulin.fyl/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/rust-argon2-0.8.3/Cargo.toml"
-    let crate_path = path
-        .replace("/Cargo.toml", ".crate")
-        .replace("/src/", "/cache/");
-    
-    let metadata = std::fs::metadata(&crate_path)?;
-    Ok(metadata.len())
-}
\ No newline at end of file
@@ -0,0 +1,397 @@
+use crate::data::cargo::cargo_home;
+use crate::error::{Errors, Result};
+use semver::{Version, VersionReq};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// What an advisory warns about, informational ones after vulnerabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdvisoryKind {
+    Notice,
+    Unmaintained,
+    Unsound,
+    Vulnerability,
+}
+
+/// The qualitative rating of a CVSS v3 base score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::None => "none",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// An advisory of the RustSec advisory database.
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: String,
+    pub kind: AdvisoryKind,
+    /// Computed from the CVSS v3 vector, `None` without one.
+    pub severity: Option<Severity>,
+    /// The version requirements of the fixed releases, as written in the advisory.
+    pub patched: Vec<String>,
+    patched_reqs: Vec<VersionReq>,
+    unaffected_reqs: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// Whether `version` is neither patched nor unaffected.
+    pub fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched_reqs
+            .iter()
+            .chain(self.unaffected_reqs.iter())
+            .any(|req| req.matches(version))
+    }
+
+    /// A short label like `high` or `unmaintained`.
+    pub fn label(&self) -> String {
+        match (self.kind, self.severity) {
+            (AdvisoryKind::Vulnerability, Some(severity)) => severity.to_string(),
+            (AdvisoryKind::Vulnerability, None) => "vulnerable".to_string(),
+            (AdvisoryKind::Unsound, _) => "unsound".to_string(),
+            (AdvisoryKind::Unmaintained, _) => "unmaintained".to_string(),
+            (AdvisoryKind::Notice, _) => "notice".to_string(),
+        }
+    }
+
+    /// Orders advisories by how urgent they are.
+    pub fn rank(&self) -> (AdvisoryKind, Option<Severity>) {
+        (self.kind, self.severity)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryEntry,
+    #[serde(default)]
+    versions: VersionsEntry,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AdvisoryEntry {
+    id: String,
+    package: String,
+    /// Only in the older `.toml` format, newer advisories have a markdown heading.
+    title: Option<String>,
+    informational: Option<String>,
+    cvss: Option<String>,
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct VersionsEntry {
+    patched: Vec<String>,
+    unaffected: Vec<String>,
+}
+
+/// The advisories of a local checkout of the RustSec advisory database, by crate name.
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDb {
+    pub advisories: Vec<Advisory>,
+    by_package: HashMap<String, Vec<usize>>,
+}
+
+impl AdvisoryDb {
+    /// Reads the database at `path`, or at the place `cargo audit` and `cargo deny` keep it if there is one.
+    pub fn load(path: Option<&str>) -> Result<Option<AdvisoryDb>> {
+        let directory = match path {
+            Some(path) if Path::new(path).join("crates").is_dir() => PathBuf::from(path),
+            Some(path) => return Err(Errors::AdvisoryDbNotFound(path.to_string())),
+            None => match default_location() {
+                Some(directory) => directory,
+                None => return Ok(None),
+            },
+        };
+        let mut res = AdvisoryDb::default();
+        for crate_dir in std::fs::read_dir(directory.join("crates"))?.flatten() {
+            let Ok(files) = std::fs::read_dir(crate_dir.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                // advisories that do not parse are skipped rather than failing the whole database
+                if let Ok(content) = std::fs::read_to_string(file.path())
+                    && let Some(advisory) = parse(&content)
+                {
+                    res.by_package
+                        .entry(advisory.package.clone())
+                        .or_default()
+                        .push(res.advisories.len());
+                    res.advisories.push(advisory);
+                }
+            }
+        }
+        Ok(Some(res))
+    }
+
+    /// The indices of the advisories affecting `version` of the crate `name`.
+    pub fn matching(&self, name: &str, version: &str) -> Vec<usize> {
+        let Ok(version) = Version::parse(version) else {
+            return Vec::new();
+        };
+        self.by_package
+            .get(name)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|index| self.advisories[*index].affects(&version))
+            .collect()
+    }
+}
+
+/// `advisory-db` in `CARGO_HOME` as `cargo audit` clones it, else the first clone of `cargo deny`.
+fn default_location() -> Option<PathBuf> {
+    let cargo_home = cargo_home()?;
+    let audit = cargo_home.join("advisory-db");
+    if audit.join("crates").is_dir() {
+        return Some(audit);
+    }
+    let mut clones: Vec<PathBuf> = std::fs::read_dir(cargo_home.join("advisory-dbs"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("crates").is_dir())
+        .collect();
+    clones.sort();
+    clones.into_iter().next()
+}
+
+/// Reads the TOML front matter of a markdown advisory, or an advisory in the older `.toml` format.
+fn parse(content: &str) -> Option<Advisory> {
+    let (front_matter, body) = match content.trim_start().strip_prefix("```toml") {
+        Some(rest) => rest.split_once("```")?,
+        None => (content, ""),
+    };
+    let file: AdvisoryFile = toml::from_str(front_matter).ok()?;
+    if file.advisory.withdrawn.is_some() {
+        return None;
+    }
+    let title = file
+        .advisory
+        .title
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string())
+        })
+        .unwrap_or_default();
+    let reqs = |versions: &[String]| {
+        versions
+            .iter()
+            .filter_map(|req| VersionReq::parse(req).ok())
+            .collect()
+    };
+    Some(Advisory {
+        id: file.advisory.id,
+        package: file.advisory.package,
+        title,
+        kind: match file.advisory.informational.as_deref() {
+            None => AdvisoryKind::Vulnerability,
+            Some("unmaintained") => AdvisoryKind::Unmaintained,
+            Some("unsound") => AdvisoryKind::Unsound,
+            Some(_) => AdvisoryKind::Notice,
+        },
+        severity: file.advisory.cvss.as_deref().and_then(cvss_severity),
+        patched_reqs: reqs(&file.versions.patched),
+        unaffected_reqs: reqs(&file.versions.unaffected),
+        patched: file.versions.patched,
+    })
+}
+
+/// Rates the base score of a `CVSS:3.x/..` vector, other versions are not rated.
+fn cvss_severity(vector: &str) -> Option<Severity> {
+    Some(match cvss_score(vector)? {
+        0.0 => Severity::None,
+        score if score < 4.0 => Severity::Low,
+        score if score < 7.0 => Severity::Medium,
+        score if score < 9.0 => Severity::High,
+        _ => Severity::Critical,
+    })
+}
+
+/// The base score of a `CVSS:3.x/..` vector.
+fn cvss_score(vector: &str) -> Option<f64> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: HashMap<&str, &str> = parts.filter_map(|part| part.split_once(':')).collect();
+    let changed = *metrics.get("S")? == "C";
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        _ => 0.2,
+    };
+    let attack_complexity = if *metrics.get("AC")? == "L" {
+        0.77
+    } else {
+        0.44
+    };
+    let privileges = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        (_, false) => 0.27,
+        (_, true) => 0.5,
+    };
+    let interaction = if *metrics.get("UI")? == "N" {
+        0.85
+    } else {
+        0.62
+    };
+    let impact_of = |metric: &str| match metrics.get(metric).copied() {
+        Some("H") => Some(0.56),
+        Some("L") => Some(0.22),
+        Some(_) => Some(0.0),
+        None => None,
+    };
+    let sub_score =
+        1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+    let impact = if changed {
+        7.52 * (sub_score - 0.029) - 3.25 * f64::powi(sub_score - 0.02, 15)
+    } else {
+        6.42 * sub_score
+    };
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * interaction;
+    let score = if impact <= 0.0 {
+        0.0
+    } else if changed {
+        f64::min(1.08 * (impact + exploitability), 10.0)
+    } else {
+        f64::min(impact + exploitability, 10.0)
+    };
+    // rounded up to one decimal the way CVSS 3.1 does it, keeping float noise out
+    Some(match (score * 100_000.0).round() as u64 {
+        scaled if scaled % 10_000 == 0 => scaled as f64 / 100_000.0,
+        scaled => (scaled / 10_000 + 1) as f64 / 10.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2021-0001"
+package = "demo"
+date = "2021-01-01"
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 1.2.3, < 2.0.0", ">= 2.1.0"]
+unaffected = ["< 1.0.0"]
+```
+
+# Use after free in `demo`
+
+Details.
+"#;
+
+    #[test]
+    fn scores_known_vectors() {
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N"),
+            Some(5.5)
+        );
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(
+            cvss_severity("CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(Severity::None)
+        );
+        assert_eq!(cvss_severity("AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(cvss_severity("CVSS:3.1/AV:N/AC:L"), None);
+    }
+
+    #[test]
+    fn scores_changed_scope_rounded_up() {
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"),
+            Some(10.0)
+        );
+        assert_eq!(
+            cvss_score("CVSS:3.0/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+        assert_eq!(
+            cvss_score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:L/I:L/A:N"),
+            Some(6.4)
+        );
+        assert_eq!(
+            cvss_severity("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"),
+            Some(Severity::Critical)
+        );
+    }
+
+    #[test]
+    fn reads_markdown_advisories() {
+        let advisory = parse(ADVISORY).unwrap();
+        assert_eq!(advisory.id, "RUSTSEC-2021-0001");
+        assert_eq!(advisory.title, "Use after free in `demo`");
+        assert_eq!(advisory.kind, AdvisoryKind::Vulnerability);
+        assert_eq!(advisory.label(), "critical");
+    }
+
+    #[test]
+    fn patched_and_unaffected_versions_are_not_affected() {
+        let advisory = parse(ADVISORY).unwrap();
+        assert!(advisory.affects(&version("1.0.0")));
+        assert!(advisory.affects(&version("1.2.2")));
+        assert!(!advisory.affects(&version("1.2.3")));
+        assert!(advisory.affects(&version("2.0.5")));
+        assert!(!advisory.affects(&version("2.1.0")));
+        assert!(!advisory.affects(&version("0.9.9")));
+    }
+
+    #[test]
+    fn reads_the_older_toml_format() {
+        let advisory = parse(
+            "[advisory]\nid = \"RUSTSEC-2018-0001\"\npackage = \"old\"\ntitle = \"Old advisory\"\n\
+             informational = \"unmaintained\"\n\n[versions]\npatched = []\n",
+        )
+        .unwrap();
+        assert_eq!(advisory.title, "Old advisory");
+        assert_eq!(advisory.label(), "unmaintained");
+        assert!(advisory.affects(&version("9.9.9")));
+    }
+
+    #[test]
+    fn skips_withdrawn_advisories() {
+        let withdrawn = ADVISORY.replace(
+            "date = \"2021-01-01\"",
+            "date = \"2021-01-01\"\nwithdrawn = \"2021-02-01\"",
+        );
+        assert!(parse(&withdrawn).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The compiled size of each package id, by profile directory like `debug` or `<triple>/release`.
+pub type ArtifactSizes = BTreeMap<String, HashMap<String, u64>>;
+
+/// File extensions of the artifacts rustc writes to `deps`.
+const EXTENSIONS: [&str; 5] = ["rlib", "rmeta", "so", "dylib", "dll"];
+
+/// A package as far as the artifact scan is concerned.
+pub struct ArtifactPackage {
+    pub id: String,
+    pub name: String,
+    pub manifest_path: String,
+}
+
+/// Sums up the artifacts in every `deps` directory below `target_directory`, per package.
+///
+/// Artifacts are mapped back to their package through the dep-info file rustc writes next to
+/// them, which lists the sources they were built from. Without one, the crate name has to be
+/// unambiguous.
+pub fn scan(target_directory: &str, packages: &[ArtifactPackage]) -> ArtifactSizes {
+    let by_dir: HashMap<PathBuf, &str> = packages
+        .iter()
+        .filter_map(|package| {
+            Some((
+                Path::new(&package.manifest_path).parent()?.to_path_buf(),
+                package.id.as_str(),
+            ))
+        })
+        .collect();
+    let mut by_crate_name: HashMap<String, Vec<&str>> = HashMap::new();
+    for package in packages {
+        by_crate_name
+            .entry(package.name.replace('-', "_"))
+            .or_default()
+            .push(package.id.as_str());
+    }
+
+    let mut res = ArtifactSizes::new();
+    for (profile, deps) in deps_dirs(Path::new(target_directory)) {
+        let Ok(entries) = std::fs::read_dir(&deps) else {
+            continue;
+        };
+        let mut owners: HashMap<String, Option<String>> = HashMap::new();
+        let sizes = res.entry(profile).or_default();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| EXTENSIONS.contains(&extension))
+            {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let stem = stem.strip_prefix("lib").unwrap_or(stem).to_string();
+            let owner = owners.entry(stem.clone()).or_insert_with(|| {
+                dep_info_owner(&deps.join(format!("{}.d", stem)), &by_dir).or_else(|| {
+                    let crate_name = stem
+                        .rsplit_once('-')
+                        .map_or(stem.as_str(), |(name, _hash)| name);
+                    match by_crate_name.get(crate_name).map(Vec::as_slice) {
+                        Some([id]) => Some(id.to_string()),
+                        _ => None,
+                    }
+                })
+            });
+            if let (Some(owner), Ok(metadata)) = (owner, entry.metadata()) {
+                *sizes.entry(owner.clone()).or_default() += metadata.len();
+            }
+        }
+    }
+    res.retain(|_profile, sizes| !sizes.is_empty());
+    res
+}
+
+/// The directories containing a `deps` directory, `target/<profile>` and `target/<triple>/<profile>`.
+fn deps_dirs(target: &Path) -> Vec<(String, PathBuf)> {
+    let mut res = Vec::new();
+    let Ok(entries) = std::fs::read_dir(target) else {
+        return res;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().join("deps").is_dir() {
+            res.push((name, entry.path().join("deps")));
+        } else if let Ok(profiles) = std::fs::read_dir(entry.path()) {
+            for profile in profiles.flatten() {
+                if profile.path().join("deps").is_dir() {
+                    res.push((
+                        format!("{}/{}", name, profile.file_name().to_string_lossy()),
+                        profile.path().join("deps"),
+                    ));
+                }
+            }
+        }
+    }
+    res.sort();
+    res
+}
+
+/// The package whose directory contains the first source listed in a dep-info file.
+fn dep_info_owner(dep_info: &Path, by_dir: &HashMap<PathBuf, &str>) -> Option<String> {
+    let content = std::fs::read_to_string(dep_info).ok()?;
+    let (_target, sources) = content.lines().next()?.split_once(": ")?;
+    // spaces inside paths are escaped with a backslash
+    let source = sources.replace("\\ ", "\u{0}");
+    let source = PathBuf::from(source.split_whitespace().next()?.replace('\u{0}', " "));
+    source
+        .ancestors()
+        .find_map(|dir| by_dir.get(dir))
+        .map(|id| id.to_string())
+}
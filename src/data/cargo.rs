@@ -0,0 +1,438 @@
+use crate::error;
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+// The model mirrors cargo's schema, the structs allowing dead code have fields the UI does not read yet.
+
+/// The document printed by `cargo metadata --format-version 1`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CargoMetadata {
+    pub packages: Vec<Package>,
+    pub workspace_members: Vec<String>,
+    /// Only emitted by cargo 1.71 and newer.
+    #[serde(default)]
+    pub workspace_default_members: Vec<String>,
+    /// Missing when cargo was invoked with `--no-deps`.
+    pub resolve: Option<Resolve>,
+    pub target_directory: String,
+    pub workspace_root: String,
+    /// The format version, only [`METADATA_VERSION`] is understood.
+    pub version: u32,
+}
+
+/// The `--format-version` of `cargo metadata` the model follows.
+pub const METADATA_VERSION: u32 = 1;
+
+/// A single package of the `packages` array.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub id: String,
+    pub license: Option<String>,
+    pub license_file: Option<String>,
+    pub description: Option<String>,
+    pub documentation: Option<String>,
+    pub repository: Option<String>,
+    /// `None` for path and workspace packages.
+    pub source: Option<String>,
+    pub manifest_path: String,
+    #[serde(default)]
+    pub dependencies: Vec<PackageDependency>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// A dependency as declared in a package's manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct PackageDependency {
+    pub name: String,
+    pub req: String,
+    #[serde(default, deserialize_with = "deserialize_kind")]
+    pub kind: DependencyKind,
+    pub rename: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default = "default_true")]
+    pub uses_default_features: bool,
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub target: Option<String>,
+    pub source: Option<String>,
+}
+
+/// A compilation target (lib, bin, test, example, bench, build script) of a package.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct Target {
+    pub name: String,
+    pub kind: Vec<String>,
+    #[serde(default)]
+    pub crate_types: Vec<String>,
+    pub src_path: String,
+    pub edition: Option<String>,
+}
+
+/// The resolved dependency graph.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct Resolve {
+    pub nodes: Vec<Node>,
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct Node {
+    pub id: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub deps: Vec<NodeDep>,
+    /// Features enabled on this package by the resolver.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// A resolved edge from a [`Node`] to one of its dependencies.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct NodeDep {
+    /// The name of the dependency as seen by the parent's code (after renames).
+    pub name: String,
+    pub pkg: String,
+    #[serde(default)]
+    pub dep_kinds: Vec<DepKindInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepKindInfo {
+    #[serde(default, deserialize_with = "deserialize_kind")]
+    pub kind: DependencyKind,
+    /// The `cfg(..)` expression or target triple the edge is restricted to.
+    pub target: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum DependencyKind {
+    #[default]
+    Normal,
+    Development,
+    Build,
+}
+
+impl DependencyKind {
+    pub const ALL: [DependencyKind; 3] = [
+        DependencyKind::Normal,
+        DependencyKind::Development,
+        DependencyKind::Build,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "normal",
+            DependencyKind::Development => "dev",
+            DependencyKind::Build => "build",
+        }
+    }
+}
+
+/// Cargo encodes normal dependencies as `null`, the others as `"dev"` and `"build"`.
+fn deserialize_kind<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DependencyKind, D::Error> {
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("normal") => Ok(DependencyKind::Normal),
+        Some("dev") => Ok(DependencyKind::Development),
+        Some("build") => Ok(DependencyKind::Build),
+        Some(other) => Err(serde::de::Error::unknown_variant(other, &["dev", "build"])),
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The platform the dependency graph is resolved for.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Platform {
+    /// The platform of the `rustc` found on the `PATH`.
+    #[default]
+    Host,
+    /// No platform filtering, like plain `cargo metadata`.
+    All,
+    Triple(String),
+}
+
+impl Platform {
+    pub fn parse(value: &str) -> Platform {
+        match value {
+            "host" => Platform::Host,
+            "all" => Platform::All,
+            triple => Platform::Triple(triple.to_string()),
+        }
+    }
+
+    /// The triple handed to `--filter-platform`, if any.
+    pub fn triple(&self) -> Option<String> {
+        match self {
+            Platform::Host => host_triple(),
+            Platform::All => None,
+            Platform::Triple(triple) => Some(triple.clone()),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Platform::Host => format!("host ({})", host_triple().unwrap_or("unknown".to_string())),
+            Platform::All => "all platforms".to_string(),
+            Platform::Triple(triple) => triple.clone(),
+        }
+    }
+
+    /// Everything the platform picker offers: host, all, then every triple rustc knows.
+    pub fn choices() -> Vec<Platform> {
+        let mut res = vec![Platform::Host, Platform::All];
+        if let Ok(output) = Command::new("rustc")
+            .arg("--print")
+            .arg("target-list")
+            .output()
+        {
+            res.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| Platform::Triple(line.trim().to_string())),
+            );
+        }
+        res
+    }
+}
+
+/// The host triple as reported by `rustc -vV`, looked up once.
+pub fn host_triple() -> Option<String> {
+    static HOST: OnceLock<Option<String>> = OnceLock::new();
+    HOST.get_or_init(|| {
+        let output = Command::new("rustc").arg("-vV").output().ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix("host: "))
+            .map(|host| host.trim().to_string())
+    })
+    .clone()
+}
+
+/// `CARGO_HOME`, or `~/.cargo` if it is not set.
+pub fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")))
+}
+
+/// Whether a package source is crates.io, through the git or the sparse index.
+pub fn is_crates_io(source: &str) -> bool {
+    source.contains("github.com/rust-lang/crates.io-index") || source.contains("index.crates.io")
+}
+
+/// Settings that change how cargo resolves the dependency graph.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataOptions {
+    pub platform: Platform,
+    /// Features activated on the root package, like `--features`.
+    pub features: BTreeSet<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+    /// The manifest the feature flags apply to, the one in the project path if `None`.
+    pub manifest_path: Option<String>,
+    /// Metadata captured earlier to read instead of running cargo, `-` for stdin.
+    pub metadata_file: Option<String>,
+    /// Build the graph from `Cargo.lock` instead of running cargo.
+    pub lockfile: bool,
+}
+
+/// An entry of the feature panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureToggle {
+    /// `--all-features`
+    All,
+    /// The inverse of `--no-default-features`
+    Default,
+    Feature(String),
+}
+
+impl FeatureToggle {
+    pub fn label(&self) -> &str {
+        match self {
+            FeatureToggle::All => "all features",
+            FeatureToggle::Default => "default",
+            FeatureToggle::Feature(name) => name,
+        }
+    }
+}
+
+impl MetadataOptions {
+    /// What the graph is read from when cargo does not resolve it, if anything.
+    pub fn fixed_source(&self) -> Option<String> {
+        match &self.metadata_file {
+            Some(file) if file == "-" => Some("stdin".to_string()),
+            Some(file) => Some(file.clone()),
+            None if self.lockfile => Some("Cargo.lock".to_string()),
+            None => None,
+        }
+    }
+
+    pub fn is_on(&self, toggle: &FeatureToggle) -> bool {
+        match toggle {
+            FeatureToggle::All => self.all_features,
+            FeatureToggle::Default => !self.no_default_features,
+            FeatureToggle::Feature(name) => self.features.contains(name),
+        }
+    }
+
+    pub fn toggle(&mut self, toggle: &FeatureToggle) {
+        match toggle {
+            FeatureToggle::All => self.all_features = !self.all_features,
+            FeatureToggle::Default => self.no_default_features = !self.no_default_features,
+            FeatureToggle::Feature(name) => {
+                if !self.features.remove(name) {
+                    self.features.insert(name.clone());
+                }
+            }
+        }
+    }
+}
+
+impl CargoMetadata {
+    /// Runs `cargo metadata` inside `path` and parses its output,
+    /// or reads the metadata file or `Cargo.lock` if the options say so.
+    pub fn load(path: &str, options: &MetadataOptions) -> error::Result<CargoMetadata> {
+        if let Some(file) = &options.metadata_file {
+            return Self::read(file);
+        }
+        if options.lockfile {
+            return Self::from_lockfile(path);
+        }
+
+        // if Cargo.toml is not found, show error
+        if !Path::new(path).join("Cargo.toml").exists() {
+            return Err(error::Errors::CargoTomlNotFound);
+        }
+
+        let mut command = Command::new("cargo");
+        command
+            .arg("metadata")
+            .arg("--format-version")
+            .arg("1")
+            .current_dir(path);
+        if let Some(triple) = options.platform.triple() {
+            command.arg("--filter-platform").arg(triple);
+        }
+        if let Some(manifest_path) = &options.manifest_path {
+            command.arg("--manifest-path").arg(manifest_path);
+        }
+        if !options.features.is_empty() {
+            command.arg("--features").arg(
+                options
+                    .features
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        if options.all_features {
+            command.arg("--all-features");
+        }
+        if options.no_default_features {
+            command.arg("--no-default-features");
+        }
+        let output = command
+            .output()
+            .map_err(|_err| error::Errors::RunCargoMetadata)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(error::Errors::CargoMetadataFailed(
+                stderr
+                    .lines()
+                    .rfind(|line| !line.trim().is_empty())
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            ));
+        }
+        Self::parse(&output.stdout)
+    }
+
+    /// Reads the output of `cargo metadata --format-version 1` from a file, or from stdin for `-`.
+    pub fn read(file: &str) -> error::Result<CargoMetadata> {
+        let json = if file == "-" {
+            let mut json = Vec::new();
+            std::io::stdin().read_to_end(&mut json)?;
+            json
+        } else {
+            std::fs::read(file)?
+        };
+        Self::parse(&json)
+    }
+
+    /// Parses the output of `cargo metadata`, refusing other format versions than [`METADATA_VERSION`].
+    pub fn parse(json: &[u8]) -> error::Result<CargoMetadata> {
+        let metadata: CargoMetadata = serde_json::from_slice(json)?;
+        if metadata.version != METADATA_VERSION {
+            return Err(error::Errors::MetadataVersion(
+                metadata.version,
+                METADATA_VERSION,
+            ));
+        }
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"{
+        "packages": [{
+            "name": "a",
+            "version": "0.1.0",
+            "id": "path+file:///a#0.1.0",
+            "manifest_path": "/a/Cargo.toml",
+            "dependencies": [{ "name": "cc", "req": "^1", "kind": "build", "rename": null, "optional": false }],
+            "targets": [{ "name": "a", "kind": ["lib"], "crate_types": ["lib"], "src_path": "/a/src/lib.rs" }]
+        }],
+        "workspace_members": ["path+file:///a#0.1.0"],
+        "resolve": null,
+        "target_directory": "/a/target",
+        "workspace_root": "/a",
+        "version": 1
+    }"#;
+
+    #[test]
+    fn parses_targets_and_declared_dependencies() {
+        let metadata = CargoMetadata::parse(METADATA.as_bytes()).unwrap();
+        let package = &metadata.packages[0];
+        assert_eq!(package.targets[0].kind, ["lib"]);
+        assert_eq!(package.targets[0].src_path, "/a/src/lib.rs");
+        assert_eq!(package.dependencies[0].kind, DependencyKind::Build);
+        assert!(package.dependencies[0].uses_default_features);
+    }
+
+    #[test]
+    fn refuses_other_format_versions() {
+        let res = CargoMetadata::parse(
+            METADATA
+                .replace("\"version\": 1", "\"version\": 2")
+                .as_bytes(),
+        );
+        assert!(matches!(res, Err(error::Errors::MetadataVersion(2, 1))));
+    }
+}
@@ -0,0 +1,425 @@
+use std::ops::AddAssign;
+use std::path::Path;
+
+/// The lines of a set of `.rs` files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Lines {
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blank: usize,
+}
+
+impl AddAssign for Lines {
+    fn add_assign(&mut self, other: Lines) {
+        self.files += other.files;
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blank += other.blank;
+    }
+}
+
+/// Whether a crate keeps itself from using `unsafe` with the `unsafe_code` lint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum UnsafeLint {
+    #[default]
+    Allowed,
+    /// `#![deny(unsafe_code)]`, which a module can still allow again.
+    Denied,
+    /// `#![forbid(unsafe_code)]`.
+    Forbidden,
+}
+
+/// The uses of the `unsafe` keyword in a set of `.rs` files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Unsafety {
+    pub blocks: usize,
+    pub functions: usize,
+    pub impls: usize,
+    pub traits: usize,
+    pub lint: UnsafeLint,
+}
+
+impl Unsafety {
+    pub fn count(&self) -> usize {
+        self.blocks + self.functions + self.impls + self.traits
+    }
+}
+
+impl AddAssign for Unsafety {
+    fn add_assign(&mut self, other: Unsafety) {
+        self.blocks += other.blocks;
+        self.functions += other.functions;
+        self.impls += other.impls;
+        self.traits += other.traits;
+        self.lint = self.lint.max(other.lint);
+    }
+}
+
+/// The Rust sources of a package, by what they are for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CodeStats {
+    /// Everything outside of `tests`, `examples` and `benches`, build scripts included.
+    pub library: Lines,
+    pub tests: Lines,
+    pub examples: Lines,
+    pub benches: Lines,
+    /// The `unsafe` code of the library, the only part dependents build.
+    pub unsafety: Unsafety,
+}
+
+impl CodeStats {
+    pub fn total(&self) -> Lines {
+        let mut res = self.library;
+        res += self.tests;
+        res += self.examples;
+        res += self.benches;
+        res
+    }
+}
+
+/// Counts the lines of every `.rs` file in the directory of the manifest at `manifest_path`.
+///
+/// Like the size, `target`, `.git` and nested packages are skipped.
+pub fn count(manifest_path: &str) -> Option<CodeStats> {
+    let package_dir = Path::new(manifest_path)
+        .parent()
+        .filter(|dir| dir.is_dir())?;
+    let mut res = CodeStats::default();
+    let mut stack = vec![package_dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = path.symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                let skipped = matches!(entry.file_name().to_str(), Some("target" | ".git"))
+                    || path.join("Cargo.toml").exists();
+                if !skipped {
+                    stack.push(path);
+                }
+            } else if metadata.is_file()
+                && path.extension().is_some_and(|extension| extension == "rs")
+                && let Ok(content) = std::fs::read_to_string(&path)
+            {
+                let (lines, code) = count_lines(&content);
+                match path
+                    .strip_prefix(package_dir)
+                    .ok()
+                    .and_then(|path| path.components().next())
+                {
+                    Some(first) if first.as_os_str() == "tests" => res.tests += lines,
+                    Some(first) if first.as_os_str() == "examples" => res.examples += lines,
+                    Some(first) if first.as_os_str() == "benches" => res.benches += lines,
+                    _ => {
+                        res.library += lines;
+                        res.unsafety += count_unsafe(&code);
+                    }
+                }
+            }
+        }
+    }
+    Some(res)
+}
+
+/// Where the scanner of [`count_lines`] is, carried over from one line to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    Code,
+    /// Inside block comments, nested this deep.
+    Comment(usize),
+    /// Inside a string literal.
+    Str,
+    /// Inside a raw string literal closed by a quote and this many `#`.
+    RawStr(usize),
+}
+
+/// Lines inside block comments and starting with `//` are comments, code after a block comment is code.
+///
+/// Returns the code without its comments and with string and char literals emptied, so neither a `"/*"` nor an
+/// `"unsafe {"` in a literal is taken for what it spells.
+fn count_lines(content: &str) -> (Lines, String) {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut res = Lines {
+        files: 1,
+        ..Lines::default()
+    };
+    let mut code = String::with_capacity(content.len());
+    let mut context = Context::Code;
+    for line in content.lines() {
+        let line: Vec<char> = line.trim().chars().collect();
+        if line.is_empty() {
+            res.blank += 1;
+            continue;
+        }
+        let mut has_code = matches!(context, Context::Str | Context::RawStr(_));
+        let mut position = 0;
+        while position < line.len() {
+            let c = line[position];
+            let next = line.get(position + 1).copied();
+            match context {
+                Context::Comment(depth) => match (c, next) {
+                    ('/', Some('*')) => {
+                        context = Context::Comment(depth + 1);
+                        position += 2;
+                    }
+                    ('*', Some('/')) => {
+                        context = if depth > 1 {
+                            Context::Comment(depth - 1)
+                        } else {
+                            Context::Code
+                        };
+                        code.push(' ');
+                        position += 2;
+                    }
+                    _ => position += 1,
+                },
+                Context::Str => {
+                    match c {
+                        '\\' => position += 1,
+                        '"' => {
+                            context = Context::Code;
+                            code.push('"');
+                        }
+                        _ => {}
+                    }
+                    position += 1;
+                }
+                Context::RawStr(hashes) => {
+                    if c == '"'
+                        && line[position + 1..]
+                            .iter()
+                            .take(hashes)
+                            .filter(|c| **c == '#')
+                            .count()
+                            == hashes
+                    {
+                        context = Context::Code;
+                        code.push('"');
+                        position += hashes;
+                    }
+                    position += 1;
+                }
+                Context::Code => match (c, next) {
+                    ('/', Some('/')) => break,
+                    ('/', Some('*')) => {
+                        context = Context::Comment(1);
+                        position += 2;
+                    }
+                    _ if c.is_whitespace() => {
+                        code.push(c);
+                        position += 1;
+                    }
+                    ('"', _) => {
+                        has_code = true;
+                        context = Context::Str;
+                        code.push('"');
+                        position += 1;
+                    }
+                    // `r"..."`, `r#"..."#` and their `br` byte string twins, but not an identifier ending in `r`
+                    ('r', Some('"' | '#'))
+                        if match position.checked_sub(1).map(|before| line[before]) {
+                            None => true,
+                            Some('b') => position < 2 || !is_ident(line[position - 2]),
+                            Some(before) => !is_ident(before),
+                        } && line[position + 1..].iter().find(|c| **c != '#') == Some(&'"') =>
+                    {
+                        let hashes = line[position + 1..]
+                            .iter()
+                            .take_while(|c| **c == '#')
+                            .count();
+                        has_code = true;
+                        context = Context::RawStr(hashes);
+                        code.push('"');
+                        position += hashes + 2;
+                    }
+                    // a char literal rather than a lifetime: `'\n'`, `'\u{..}'` or a single char like `'"'`
+                    ('\'', Some('\\')) => {
+                        has_code = true;
+                        code.push_str("''");
+                        position = line
+                            .iter()
+                            .skip(position + 3)
+                            .position(|c| *c == '\'')
+                            .map_or(line.len(), |close| position + 3 + close + 1);
+                    }
+                    ('\'', Some(_)) if line.get(position + 2) == Some(&'\'') => {
+                        has_code = true;
+                        code.push_str("''");
+                        position += 3;
+                    }
+                    _ => {
+                        has_code = true;
+                        code.push(c);
+                        position += 1;
+                    }
+                },
+            }
+        }
+        code.push('\n');
+        if has_code {
+            res.code += 1;
+        } else {
+            res.comments += 1;
+        }
+    }
+    (res, code)
+}
+
+/// Tells `unsafe` blocks, functions, impls and traits apart by what follows the keyword.
+fn count_unsafe(code: &str) -> Unsafety {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut res = Unsafety::default();
+    for (position, _keyword) in code.match_indices("unsafe") {
+        let after = &code[position + "unsafe".len()..];
+        if code[..position].chars().next_back().is_some_and(is_ident)
+            || after.chars().next().is_some_and(is_ident)
+        {
+            continue;
+        }
+        let mut next = after.trim_start();
+        // `unsafe extern "C" fn` is a function, `unsafe extern "C" { .. }` a block
+        if let Some(rest) = next.strip_prefix("extern") {
+            next = rest.trim_start();
+            if let Some(abi) = next.strip_prefix('"') {
+                next = abi
+                    .split_once('"')
+                    .map_or("", |(_abi, rest)| rest)
+                    .trim_start();
+            }
+        }
+        let word = next
+            .split(|c: char| !is_ident(c))
+            .next()
+            .unwrap_or_default();
+        match word {
+            "fn" => res.functions += 1,
+            "impl" => res.impls += 1,
+            "trait" => res.traits += 1,
+            _ if next.starts_with('{') => res.blocks += 1,
+            _ => {}
+        }
+    }
+    for (position, _attribute) in code.match_indices("#![") {
+        let attribute: String = code[position + 3..]
+            .split(']')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let lint = match attribute.split_once('(') {
+            Some(("forbid", lints)) if lints.contains("unsafe_code") => UnsafeLint::Forbidden,
+            Some(("deny", lints)) if lints.contains("unsafe_code") => UnsafeLint::Denied,
+            _ => continue,
+        };
+        res.lint = res.lint.max(lint);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unsafety(content: &str) -> Unsafety {
+        count_unsafe(&count_lines(content).1)
+    }
+
+    fn lines(content: &str) -> (usize, usize, usize) {
+        let (res, _code) = count_lines(content);
+        (res.code, res.comments, res.blank)
+    }
+
+    #[test]
+    fn counts_code_comment_and_blank_lines() {
+        assert_eq!(
+            lines("// a comment\n\nfn main() {} // trailing\n/* block\nstill */ let x = 1;\n"),
+            (2, 2, 1)
+        );
+        assert_eq!(lines("/* outer /* inner */\nstill outer */\n"), (0, 2, 0));
+    }
+
+    #[test]
+    fn comment_openers_in_literals_are_code() {
+        assert_eq!(
+            lines("let glob = \"src/*.rs\";\nlet x = 1;\n// comment\n"),
+            (2, 1, 0)
+        );
+        assert_eq!(
+            lines("let url = \"https://example.com\"; let y = 2;\n"),
+            (1, 0, 0)
+        );
+        assert_eq!(lines("let s = r#\"/* \"# ;\nlet x = 1;\n"), (2, 0, 0));
+        assert_eq!(lines("let c = '/'; let d = '*';\nlet x = 1;\n"), (2, 0, 0));
+    }
+
+    #[test]
+    fn lines_inside_multi_line_strings_are_code() {
+        assert_eq!(lines("let s = \"first\n// not a comment\n\";\n"), (3, 0, 0));
+        assert_eq!(
+            lines("let s = r\"\n/* not a comment\n\";\nlet x = 1;\n"),
+            (4, 0, 0)
+        );
+    }
+
+    #[test]
+    fn comment_openers_in_strings_do_not_hide_unsafe() {
+        let content =
+            "let glob = \"src/*.rs\";\nunsafe { work() }\nlet open = \"/*\";\nunsafe fn f() {}\n";
+        let res = unsafety(content);
+        assert_eq!(res.blocks, 1);
+        assert_eq!(res.functions, 1);
+    }
+
+    #[test]
+    fn unsafe_in_literals_is_not_counted() {
+        assert_eq!(unsafety("let s = \"unsafe { }\";\n").count(), 0);
+        assert_eq!(
+            unsafety("let s = r#\"a \"quoted\" unsafe { \"#;\nunsafe impl Send for X {}\n").impls,
+            1
+        );
+        assert_eq!(unsafety("let s = br\"unsafe {\";\n").count(), 0);
+        assert_eq!(unsafety("let s = \"first\nunsafe { }\n\";\n").count(), 0);
+    }
+
+    #[test]
+    fn char_literals_and_lifetimes() {
+        assert_eq!(
+            unsafety("let q = '\"'; unsafe impl Send for X {}\n").impls,
+            1
+        );
+        assert_eq!(unsafety("let q = '\\''; unsafe { }\n").blocks, 1);
+        assert_eq!(unsafety("fn f<'a>(x: &'a str) { unsafe { } }\n").blocks, 1);
+        assert_eq!(
+            unsafety("fn f(x: &'static str) {} // unsafe {\n").count(),
+            0
+        );
+    }
+
+    #[test]
+    fn unsafe_in_comments_is_not_counted() {
+        assert_eq!(unsafety("/* /* */ unsafe { */\n").count(), 0);
+        assert_eq!(unsafety("/* \"unclosed */ unsafe { }\n").blocks, 1);
+    }
+
+    #[test]
+    fn unsafe_code_lint() {
+        assert_eq!(
+            unsafety("#![forbid(unsafe_code)]\n").lint,
+            UnsafeLint::Forbidden
+        );
+        assert_eq!(
+            unsafety("#![deny(missing_docs, unsafe_code)]\n").lint,
+            UnsafeLint::Denied
+        );
+        assert_eq!(
+            unsafety("let s = \"#![forbid(unsafe_code)]\";\n").lint,
+            UnsafeLint::Allowed
+        );
+    }
+}
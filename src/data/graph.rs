@@ -0,0 +1,835 @@
+use crate::data::{CargoMetadata, DependencyKind, Metadata};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::{Index, IndexMut};
+use std::path::Path;
+
+/// The id of the synthetic package whose dependencies are those of every workspace member.
+pub const WORKSPACE_ID: &str = "(workspace)";
+
+/// How many unfinished paths [`Graph::paths`] keeps before giving up on finding more.
+const PATH_SEARCH_LIMIT: usize = 100_000;
+
+/// How an edge is used: its kind and the interned platform it is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeKind {
+    pub kind: DependencyKind,
+    /// Index into [`Graph::platform`], `None` if unconditional.
+    pub platform: Option<usize>,
+}
+
+/// A resolved edge to another package of the graph.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    /// The package on the other end, the dependency or the dependent.
+    pub index: usize,
+    pub kinds: Vec<EdgeKind>,
+}
+
+/// How a package is reached from the start of a traversal.
+#[derive(Debug, Default)]
+pub struct Reach {
+    pub kinds: BTreeSet<DependencyKind>,
+    pub platforms: BTreeSet<Option<usize>>,
+}
+
+/// A set of packages by graph index, one bit each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexSet {
+    words: Vec<u64>,
+}
+
+impl IndexSet {
+    /// An empty set for the indices below `len`.
+    pub fn new(len: usize) -> IndexSet {
+        IndexSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    /// Adds every index of `other`, returning whether any was new.
+    pub fn union_with(&mut self, other: &IndexSet) -> bool {
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            changed |= *other & !*word != 0;
+            *word |= *other;
+        }
+        changed
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(number, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| number * 64 + bit)
+        })
+    }
+}
+
+/// The dependency graph, packages are addressed by their index.
+#[derive(Debug, Default)]
+pub struct Graph {
+    packages: Vec<Metadata>,
+    ids: HashMap<String, usize>,
+    /// Every version of a crate, by name.
+    names: HashMap<String, Vec<usize>>,
+    platforms: Vec<String>,
+    platform_ids: HashMap<String, usize>,
+    dependencies: Vec<Vec<Edge>>,
+    dependents: Vec<Vec<Edge>>,
+}
+
+impl Index<usize> for Graph {
+    type Output = Metadata;
+
+    fn index(&self, index: usize) -> &Metadata {
+        &self.packages[index]
+    }
+}
+
+impl IndexMut<usize> for Graph {
+    fn index_mut(&mut self, index: usize) -> &mut Metadata {
+        &mut self.packages[index]
+    }
+}
+
+impl Graph {
+    /// Builds the graph of resolved metadata, taking what is already known about a package from `previous`.
+    ///
+    /// Workspaces with more than one member get the synthetic [`WORKSPACE_ID`] package, whose
+    /// dependencies are the union of the dependencies of all members.
+    pub fn build(metadata: &CargoMetadata, previous: &Graph) -> Graph {
+        let mut res = Graph::default();
+        for package in &metadata.packages {
+            res.add_package(Metadata {
+                id: package.id.clone(),
+                size: previous
+                    .get(&package.id)
+                    .map(|known| known.size)
+                    .unwrap_or_default(),
+                code: previous.get(&package.id).and_then(|known| known.code),
+                artifacts: previous
+                    .get(&package.id)
+                    .map(|known| known.artifacts.clone())
+                    .unwrap_or_default(),
+                timing: previous.get(&package.id).and_then(|known| known.timing),
+                updates: previous
+                    .get(&package.id)
+                    .and_then(|known| known.updates.clone()),
+                name: package.name.clone(),
+                version: package.version.clone(),
+                license: package.license.clone().unwrap_or_default(),
+                license_file: package.license_file.clone().unwrap_or_default(),
+                source: package.source.clone(),
+                documentation: package.documentation.clone().unwrap_or_default(),
+                description: package.description.clone().unwrap_or_default(),
+                manifest_path: package.manifest_path.clone(),
+                features: package.features.clone(),
+                dependency_features: package.dependencies.iter().fold(
+                    BTreeMap::new(),
+                    |mut res, dependency| {
+                        let features: &mut BTreeSet<String> =
+                            res.entry(dependency.name.clone()).or_default();
+                        features.extend(dependency.features.iter().cloned());
+                        if dependency.uses_default_features {
+                            features.insert("default".to_string());
+                        }
+                        res
+                    },
+                ),
+                ..Metadata::default()
+            });
+        }
+
+        for node in metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| resolve.nodes.iter())
+        {
+            let Some(from) = res.index_of(&node.id) else {
+                continue;
+            };
+            res.packages[from].enabled_features = node.features.clone();
+            for dep in &node.deps {
+                let Some(to) = res.index_of(&dep.pkg) else {
+                    continue;
+                };
+                for info in &dep.dep_kinds {
+                    res.add_dependency(from, to, info.kind, info.target.as_deref());
+                }
+            }
+        }
+
+        // the whole workspace is a synthetic package whose dependencies are the union of the
+        // dependencies of all members, with every kind they are declared with
+        if metadata.workspace_members.len() > 1 {
+            let root = res.add_package(Metadata {
+                id: WORKSPACE_ID.to_string(),
+                name: "workspace".to_string(),
+                description: format!(
+                    "All {} members of the workspace",
+                    metadata.workspace_members.len()
+                ),
+                manifest_path: Path::new(&metadata.workspace_root)
+                    .join("Cargo.toml")
+                    .to_string_lossy()
+                    .to_string(),
+                ..Metadata::default()
+            });
+            let members: BTreeSet<usize> = metadata
+                .workspace_members
+                .iter()
+                .filter_map(|id| res.index_of(id))
+                .collect();
+            let mut edges: BTreeMap<usize, BTreeSet<EdgeKind>> = BTreeMap::new();
+            for member in &members {
+                for edge in &res.dependencies[*member] {
+                    edges.entry(edge.index).or_default().extend(&edge.kinds);
+                }
+            }
+            for (to, kinds) in edges {
+                for kind in kinds {
+                    res.push_edge(root, to, kind);
+                }
+            }
+        }
+        res
+    }
+
+    /// Adds a package without edges, unless a package with the same id exists already.
+    pub fn add_package(&mut self, metadata: Metadata) -> usize {
+        if let Some(index) = self.index_of(&metadata.id) {
+            return index;
+        }
+        let index = self.packages.len();
+        self.ids.insert(metadata.id.clone(), index);
+        self.names
+            .entry(metadata.name.clone())
+            .or_default()
+            .push(index);
+        self.packages.push(metadata);
+        self.dependencies.push(Vec::new());
+        self.dependents.push(Vec::new());
+        index
+    }
+
+    pub fn add_dependency(
+        &mut self,
+        from: usize,
+        to: usize,
+        kind: DependencyKind,
+        platform: Option<&str>,
+    ) {
+        let platform = platform.map(|platform| self.intern_platform(platform));
+        self.push_edge(from, to, EdgeKind { kind, platform });
+    }
+
+    fn push_edge(&mut self, from: usize, to: usize, kind: EdgeKind) {
+        for (edges, other) in [
+            (&mut self.dependencies[from], to),
+            (&mut self.dependents[to], from),
+        ] {
+            match edges.iter_mut().find(|edge| edge.index == other) {
+                Some(edge) if !edge.kinds.contains(&kind) => edge.kinds.push(kind),
+                Some(_edge) => {}
+                None => edges.push(Edge {
+                    index: other,
+                    kinds: vec![kind],
+                }),
+            }
+        }
+    }
+
+    fn intern_platform(&mut self, platform: &str) -> usize {
+        if let Some(index) = self.platform_ids.get(platform) {
+            return *index;
+        }
+        self.platforms.push(platform.to_string());
+        self.platform_ids
+            .insert(platform.to_string(), self.platforms.len() - 1);
+        self.platforms.len() - 1
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.ids.get(id).copied()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Metadata> {
+        self.index_of(id).map(|index| &self.packages[index])
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    pub fn packages(&self) -> impl Iterator<Item = (usize, &Metadata)> {
+        self.packages.iter().enumerate()
+    }
+
+    pub fn packages_mut(&mut self) -> impl Iterator<Item = &mut Metadata> {
+        self.packages.iter_mut()
+    }
+
+    /// The interned `cfg(..)` expression or target triple.
+    pub fn platform(&self, index: usize) -> &str {
+        &self.platforms[index]
+    }
+
+    pub fn dependencies(&self, index: usize) -> &[Edge] {
+        &self.dependencies[index]
+    }
+
+    pub fn dependents(&self, index: usize) -> &[Edge] {
+        &self.dependents[index]
+    }
+
+    /// Every crate name together with the packages resolved for it.
+    pub fn names(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.names
+            .iter()
+            .map(|(name, indices)| (name.as_str(), indices.as_slice()))
+    }
+
+    /// Every resolved version of the crate called `name`.
+    pub fn versions(&self, name: &str) -> &[usize] {
+        self.names.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Collects the packages below `from` together with how they are reached from it.
+    ///
+    /// Dev-dependencies only count on the first edge, as cargo never builds them for dependencies.
+    /// Anything reached through a build-dependency is itself only needed at build time, and
+    /// anything reached through a platform specific edge only exists on that platform.
+    pub fn reach(&self, from: usize, direct: bool) -> HashMap<usize, Reach> {
+        let mut res: HashMap<usize, Reach> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(usize, EdgeKind)> = self
+            .dependencies(from)
+            .iter()
+            .flat_map(|edge| edge.kinds.iter().map(|kind| (edge.index, *kind)))
+            .collect();
+        while let Some((index, reached)) = stack.pop() {
+            if !visited.insert((index, reached)) {
+                continue;
+            }
+            let reach = res.entry(index).or_default();
+            reach.kinds.insert(reached.kind);
+            reach.platforms.insert(reached.platform);
+            if direct {
+                continue;
+            }
+            for edge in self.dependencies(index) {
+                for info in &edge.kinds {
+                    let Some(kind) = reached_through(reached.kind, info.kind) else {
+                        continue;
+                    };
+                    let platform = reached.platform.or(info.platform);
+                    stack.push((edge.index, EdgeKind { kind, platform }));
+                }
+            }
+        }
+        res
+    }
+
+    /// Collects the packages depending on `from`, the synthetic workspace package aside, together
+    /// with how they reach it, following the same rules as [`Graph::reach`] from their side.
+    pub fn reach_dependents(&self, from: usize, direct: bool) -> HashMap<usize, Reach> {
+        let mut res: HashMap<usize, Reach> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(usize, EdgeKind)> = self
+            .dependents(from)
+            .iter()
+            .flat_map(|edge| edge.kinds.iter().map(|kind| (edge.index, *kind)))
+            .collect();
+        while let Some((index, reached)) = stack.pop() {
+            if self.packages[index].id == WORKSPACE_ID || !visited.insert((index, reached)) {
+                continue;
+            }
+            let reach = res.entry(index).or_default();
+            reach.kinds.insert(reached.kind);
+            reach.platforms.insert(reached.platform);
+            // a dev-dependency has to be the first edge, nothing can depend on it any further
+            if direct || reached.kind == DependencyKind::Development {
+                continue;
+            }
+            for edge in self.dependents(index) {
+                for info in &edge.kinds {
+                    let kind = match info.kind {
+                        DependencyKind::Normal => reached.kind,
+                        edge_kind => edge_kind,
+                    };
+                    let platform = info.platform.or(reached.platform);
+                    stack.push((edge.index, EdgeKind { kind, platform }));
+                }
+            }
+        }
+        res
+    }
+
+    /// Every package together with everything building it builds: its normal and build
+    /// dependencies, transitively.
+    ///
+    /// The sets are joined in postorder, so an acyclic graph takes one pass and a second one
+    /// to see that nothing changes.
+    pub fn closures(&self) -> Vec<IndexSet> {
+        let built = |edge: &&Edge| {
+            edge.kinds
+                .iter()
+                .any(|kind| kind.kind != DependencyKind::Development)
+        };
+
+        let mut postorder = Vec::new();
+        let mut visited = vec![false; self.len()];
+        for start in 0..self.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some((index, next_edge)) = stack.pop() {
+                match self.dependencies(index).get(next_edge) {
+                    Some(edge) => {
+                        stack.push((index, next_edge + 1));
+                        if built(&edge) && !visited[edge.index] {
+                            visited[edge.index] = true;
+                            stack.push((edge.index, 0));
+                        }
+                    }
+                    None => postorder.push(index),
+                }
+            }
+        }
+
+        let mut res: Vec<IndexSet> = (0..self.len())
+            .map(|index| {
+                let mut closure = IndexSet::new(self.len());
+                closure.insert(index);
+                closure
+            })
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in &postorder {
+                for edge in self.dependencies(*index).iter().filter(built) {
+                    if edge.index == *index {
+                        continue;
+                    }
+                    let dependency = std::mem::take(&mut res[edge.index]);
+                    changed |= res[*index].union_with(&dependency);
+                    res[edge.index] = dependency;
+                }
+            }
+        }
+        res
+    }
+
+    /// The paths from `from` to `to` visiting no package twice, shortest first, at most `limit` of them.
+    ///
+    /// Like in [`Graph::reach`], dev-dependencies only count on the first edge.
+    pub fn paths(&self, from: usize, to: usize, limit: usize) -> Vec<Vec<usize>> {
+        // only packages that lead to `to` at all are worth following
+        let mut leading = HashSet::from([to]);
+        let mut stack = vec![to];
+        while let Some(index) = stack.pop() {
+            for edge in self.dependents(index) {
+                if leading.insert(edge.index) {
+                    stack.push(edge.index);
+                }
+            }
+        }
+
+        let mut res = Vec::new();
+        let mut queue = VecDeque::from([vec![from]]);
+        while let Some(path) = queue.pop_front() {
+            let last = path[path.len() - 1];
+            if last == to {
+                res.push(path);
+                if res.len() >= limit {
+                    break;
+                }
+                continue;
+            }
+            for edge in self.dependencies(last) {
+                let usable = path.len() == 1
+                    || edge
+                        .kinds
+                        .iter()
+                        .any(|kind| kind.kind != DependencyKind::Development);
+                if usable
+                    && leading.contains(&edge.index)
+                    && !path.contains(&edge.index)
+                    && queue.len() < PATH_SEARCH_LIMIT
+                {
+                    let mut next = path.clone();
+                    next.push(edge.index);
+                    queue.push_back(next);
+                }
+            }
+        }
+        res
+    }
+
+    /// The immediate dominator of every package reachable from `root`: the package closest to it
+    /// that every path from `root` goes through. `root` dominates itself.
+    ///
+    /// Uses the iterative algorithm by Cooper, Harvey and Kennedy. An edge is followed when it
+    /// reaches its package with one of `kinds`, the kind being carried down like [`Graph::reach`]
+    /// does. Also returns the reachable packages in reverse postorder.
+    pub fn dominators(
+        &self,
+        root: usize,
+        kinds: &BTreeSet<DependencyKind>,
+    ) -> (Vec<Option<usize>>, Vec<usize>) {
+        // the shown kinds every package is reached with
+        let mut reached = vec![BTreeSet::new(); self.len()];
+        let mut stack: Vec<(usize, DependencyKind)> = self
+            .dependencies(root)
+            .iter()
+            .flat_map(|edge| edge.kinds.iter().map(|kind| (edge.index, kind.kind)))
+            .filter(|(_, kind)| kinds.contains(kind))
+            .collect();
+        while let Some((index, kind)) = stack.pop() {
+            if !reached[index].insert(kind) {
+                continue;
+            }
+            for edge in self.dependencies(index) {
+                for info in &edge.kinds {
+                    if let Some(next) = reached_through(kind, info.kind)
+                        && kinds.contains(&next)
+                    {
+                        stack.push((edge.index, next));
+                    }
+                }
+            }
+        }
+        let usable = |from: usize, edge: &Edge| {
+            edge.kinds.iter().any(|info| {
+                if from == root {
+                    return kinds.contains(&info.kind);
+                }
+                reached[from].iter().any(|kind| {
+                    reached_through(*kind, info.kind).is_some_and(|next| kinds.contains(&next))
+                })
+            })
+        };
+
+        // reverse postorder by an iterative depth-first search
+        let mut postorder = Vec::new();
+        let mut visited = vec![false; self.len()];
+        let mut stack = vec![(root, 0)];
+        visited[root] = true;
+        while let Some((index, next_edge)) = stack.pop() {
+            match self.dependencies(index).get(next_edge) {
+                Some(edge) => {
+                    stack.push((index, next_edge + 1));
+                    if usable(index, edge) && !visited[edge.index] {
+                        visited[edge.index] = true;
+                        stack.push((edge.index, 0));
+                    }
+                }
+                None => postorder.push(index),
+            }
+        }
+        let order: Vec<usize> = postorder.into_iter().rev().collect();
+        let mut position = vec![usize::MAX; self.len()];
+        for (number, index) in order.iter().enumerate() {
+            position[*index] = number;
+        }
+
+        let mut res = vec![None; self.len()];
+        res[root] = Some(root);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in order.iter().skip(1) {
+                let mut new_idom: Option<usize> = None;
+                for edge in self.dependents(*index) {
+                    let parent = edge.index;
+                    let usable = self
+                        .dependencies(parent)
+                        .iter()
+                        .any(|edge| edge.index == *index && usable(parent, edge));
+                    if position[parent] == usize::MAX || res[parent].is_none() || !usable {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => parent,
+                        Some(mut a) => {
+                            let mut b = parent;
+                            while a != b {
+                                while position[a] > position[b] {
+                                    a = res[a].unwrap_or(root);
+                                }
+                                while position[b] > position[a] {
+                                    b = res[b].unwrap_or(root);
+                                }
+                            }
+                            a
+                        }
+                    });
+                }
+                if new_idom.is_some() && res[*index] != new_idom {
+                    res[*index] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        (res, order)
+    }
+
+    /// How many packages depend on the package at `index` directly, the synthetic workspace package aside.
+    pub fn dependent_count(&self, index: usize) -> usize {
+        self.dependents(index)
+            .iter()
+            .filter(|edge| self.packages[edge.index].id != WORKSPACE_ID)
+            .count()
+    }
+}
+
+/// The kind a package is reached with through an edge of `edge` kind from a package reached
+/// with `reached`: everything below a build-dependency is built, and dev-dependencies are only
+/// followed from the root.
+fn reached_through(reached: DependencyKind, edge: DependencyKind) -> Option<DependencyKind> {
+    match (reached, edge) {
+        (_, DependencyKind::Development) => None,
+        (DependencyKind::Normal, edge) => Some(edge),
+        (reached, _) => Some(reached),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A graph of `count` packages named by their index.
+    fn graph(count: usize, edges: &[(usize, usize, DependencyKind)]) -> Graph {
+        let mut res = Graph::default();
+        for index in 0..count {
+            res.add_package(Metadata {
+                id: index.to_string(),
+                ..Metadata::default()
+            });
+        }
+        for (from, to, kind) in edges {
+            res.add_dependency(*from, *to, *kind, None);
+        }
+        res
+    }
+
+    fn dominators(graph: &Graph, kinds: &[DependencyKind]) -> Vec<Option<usize>> {
+        graph.dominators(0, &kinds.iter().copied().collect()).0
+    }
+
+    const NORMAL: DependencyKind = DependencyKind::Normal;
+    const DEV: DependencyKind = DependencyKind::Development;
+
+    #[test]
+    fn dominators_of_a_diamond() {
+        let graph = graph(
+            5,
+            &[
+                (0, 1, NORMAL),
+                (0, 2, NORMAL),
+                (1, 3, NORMAL),
+                (2, 3, NORMAL),
+                (3, 4, NORMAL),
+            ],
+        );
+        assert_eq!(
+            dominators(&graph, &[NORMAL]),
+            [Some(0), Some(0), Some(0), Some(0), Some(3)]
+        );
+    }
+
+    #[test]
+    fn dominators_of_a_shared_subtree() {
+        let graph = graph(
+            6,
+            &[
+                (0, 1, NORMAL),
+                (0, 2, NORMAL),
+                (1, 3, NORMAL),
+                (3, 4, NORMAL),
+                (3, 5, NORMAL),
+                (2, 5, NORMAL),
+            ],
+        );
+        let (dominators, order) = graph.dominators(0, &BTreeSet::from([NORMAL]));
+        assert_eq!(
+            dominators,
+            [Some(0), Some(0), Some(0), Some(1), Some(3), Some(0)]
+        );
+        assert_eq!(order[0], 0);
+        assert_eq!(order.len(), 6);
+    }
+
+    #[test]
+    fn dominators_follow_the_shown_kinds() {
+        let graph = graph(
+            5,
+            &[
+                (0, 1, NORMAL),
+                (0, 2, DEV),
+                (1, 3, NORMAL),
+                (2, 3, NORMAL),
+                (1, 4, DEV),
+            ],
+        );
+        assert_eq!(
+            dominators(&graph, &[NORMAL, DEV]),
+            [Some(0), Some(0), Some(0), Some(0), None]
+        );
+        assert_eq!(
+            dominators(&graph, &[NORMAL]),
+            [Some(0), Some(0), None, Some(1), None]
+        );
+    }
+
+    #[test]
+    fn dominators_carry_the_reached_kind_down() {
+        let graph = graph(3, &[(0, 1, DependencyKind::Build), (1, 2, NORMAL)]);
+        assert_eq!(
+            dominators(&graph, &[DependencyKind::Build]),
+            [Some(0), Some(0), Some(1)]
+        );
+        assert_eq!(dominators(&graph, &[NORMAL]), [Some(0), None, None]);
+    }
+
+    #[test]
+    fn closures_skip_dev_dependencies_and_survive_cycles() {
+        let graph = graph(
+            5,
+            &[
+                (0, 1, NORMAL),
+                (1, 2, DependencyKind::Build),
+                (2, 1, NORMAL),
+                (0, 3, DEV),
+                (3, 4, NORMAL),
+            ],
+        );
+        let closures: Vec<Vec<usize>> = graph
+            .closures()
+            .iter()
+            .map(|closure| closure.iter().collect())
+            .collect();
+        assert_eq!(
+            closures,
+            [vec![0, 1, 2], vec![1, 2], vec![1, 2], vec![3, 4], vec![4]]
+        );
+    }
+
+    #[test]
+    fn closures_are_complete_on_wide_graphs() {
+        // a chain spanning several words of bits
+        let edges: Vec<_> = (0..199).map(|index| (index + 1, index, NORMAL)).collect();
+        let closures = graph(200, &edges).closures();
+        assert_eq!(closures[199].len(), 200);
+        assert_eq!(
+            closures[70].iter().collect::<Vec<_>>(),
+            (0..=70).collect::<Vec<_>>()
+        );
+    }
+
+    /// Resolved metadata of a workspace, every package depending on the named ones.
+    fn workspace(
+        members: &[&str],
+        packages: &[(&str, &[(&str, DependencyKind)])],
+    ) -> CargoMetadata {
+        let kind = |kind: &DependencyKind| match kind {
+            DependencyKind::Normal => serde_json::Value::Null,
+            kind => kind.as_str().into(),
+        };
+        let json = serde_json::json!({
+            "packages": packages.iter().map(|(name, _dependencies)| serde_json::json!({
+                "name": name,
+                "version": "0.1.0",
+                "id": name,
+                "manifest_path": "",
+            })).collect::<Vec<_>>(),
+            "workspace_members": members,
+            "resolve": {
+                "nodes": packages.iter().map(|(name, dependencies)| serde_json::json!({
+                    "id": name,
+                    "deps": dependencies.iter().map(|(dependency, dependency_kind)| serde_json::json!({
+                        "name": dependency,
+                        "pkg": dependency,
+                        "dep_kinds": [{ "kind": kind(dependency_kind) }],
+                    })).collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+            },
+            "target_directory": "",
+            "workspace_root": "",
+            "version": 1,
+        });
+        CargoMetadata::parse(json.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn the_workspace_depends_on_the_union_of_its_members() {
+        let metadata = workspace(
+            &["a", "b", "c"],
+            &[
+                (
+                    "a",
+                    &[
+                        ("b", DependencyKind::Build),
+                        ("log", NORMAL),
+                        ("tempfile", DEV),
+                    ],
+                ),
+                ("b", &[("log", NORMAL), ("c", DEV)]),
+                ("c", &[("log", DependencyKind::Build)]),
+                ("log", &[]),
+                ("tempfile", &[]),
+            ],
+        );
+        let graph = Graph::build(&metadata, &Graph::default());
+        let root = graph.index_of(WORKSPACE_ID).unwrap();
+        let listed: Vec<(&str, Vec<DependencyKind>)> = graph
+            .dependencies(root)
+            .iter()
+            .map(|edge| {
+                (
+                    graph[edge.index].id.as_str(),
+                    edge.kinds.iter().map(|kind| kind.kind).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("b", vec![DependencyKind::Build]),
+                ("c", vec![DEV]),
+                ("log", vec![NORMAL, DependencyKind::Build]),
+                ("tempfile", vec![DEV])
+            ]
+        );
+
+        let log = graph.index_of("log").unwrap();
+        assert_eq!(graph.dependent_count(log), 3);
+        let reached = graph.reach(root, false);
+        assert!(reached[&log].kinds.contains(&NORMAL));
+        assert!(!reached.contains_key(&graph.index_of("a").unwrap()));
+    }
+
+    #[test]
+    fn single_member_workspaces_have_no_synthetic_root() {
+        let graph = Graph::build(&workspace(&["a"], &[("a", &[])]), &Graph::default());
+        assert!(graph.index_of(WORKSPACE_ID).is_none());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::data::cargo::cargo_home;
+use crate::error::{Errors, Result};
+use semver::{Version, VersionReq};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A release of a crate as listed in the registry index.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: Version,
+    pub yanked: bool,
+}
+
+/// The newer releases of a resolved package, only set where there is one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Updates {
+    /// The newest release the version requirement `^<resolved>` allows.
+    pub compatible: Option<Version>,
+    /// The newest release of all.
+    pub latest: Option<Version>,
+    /// Whether the resolved version itself is yanked.
+    pub yanked: bool,
+    /// The compatible release closest to a yanked version that is not yanked, newer ones first.
+    pub replacement: Option<Version>,
+}
+
+/// How far a newer release is from the resolved one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// Cargo takes the leftmost non-zero component for the major one, so `0.3` to `0.4` and `0.0.1` to `0.0.2` break.
+    pub fn between(from: &Version, to: &Version) -> Bump {
+        let (breaking, minor) = match (from.major, from.minor) {
+            (0, 0) => ((to.major, to.minor, to.patch) != (0, 0, from.patch), false),
+            (0, minor) => ((to.major, to.minor) != (0, minor), to.patch != from.patch),
+            (major, minor) => (to.major != major, to.minor != minor),
+        };
+        if breaking {
+            Bump::Major
+        } else if minor {
+            Bump::Minor
+        } else {
+            Bump::Patch
+        }
+    }
+}
+
+/// A crates.io package to look up in the index.
+pub struct RegistryPackage {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// The updates of every crates.io package, by package id.
+pub type AllUpdates = HashMap<String, Updates>;
+
+/// The crates.io index on this machine, read from cargo's index cache or a plain checkout.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryIndex {
+    /// `.cache` directories of cargo, one entry per crate with a binary header.
+    caches: Vec<PathBuf>,
+    /// Checkouts with one JSON line per release, like a mirror of the index repository.
+    checkouts: Vec<PathBuf>,
+}
+
+impl RegistryIndex {
+    /// Uses the index at `path`, or the crates.io caches in `CARGO_HOME/registry/index`.
+    pub fn locate(path: Option<&str>) -> Result<RegistryIndex> {
+        let mut res = RegistryIndex::default();
+        match path {
+            Some(path) if Path::new(path).join(".cache").is_dir() => {
+                res.caches.push(Path::new(path).join(".cache"))
+            }
+            Some(path) if Path::new(path).is_dir() => res.checkouts.push(PathBuf::from(path)),
+            Some(path) => return Err(Errors::RegistryIndexNotFound(path.to_string())),
+            None => {
+                let Some(index_dir) = cargo_home().map(|home| home.join("registry").join("index"))
+                else {
+                    return Ok(res);
+                };
+                // the sparse index first, the git index only was the default before cargo 1.70
+                for name in ["index.crates.io-", "github.com-"] {
+                    let Ok(entries) = std::fs::read_dir(&index_dir) else {
+                        continue;
+                    };
+                    res.caches.extend(
+                        entries
+                            .flatten()
+                            .filter(|entry| entry.file_name().to_string_lossy().starts_with(name))
+                            .map(|entry| entry.path().join(".cache"))
+                            .filter(|cache| cache.is_dir()),
+                    );
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Every release of the crate `name` the index knows, empty if it is not cached.
+    pub fn releases(&self, name: &str) -> Vec<Release> {
+        let relative = index_path(&name.to_lowercase());
+        let from_cache = self
+            .caches
+            .iter()
+            .find_map(|cache| std::fs::read(cache.join(&relative)).ok())
+            .map(|content| cache_entries(&content));
+        let entries = from_cache.or_else(|| {
+            self.checkouts
+                .iter()
+                .find_map(|checkout| std::fs::read_to_string(checkout.join(&relative)).ok())
+                .map(|content| content.lines().map(str::to_string).collect())
+        });
+        entries
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|entry| serde_json::from_str::<IndexEntry>(entry).ok())
+            .filter_map(|entry| {
+                Some(Release {
+                    version: Version::parse(&entry.vers).ok()?,
+                    yanked: entry.yanked,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// `1/a`, `2/ab`, `3/a/abc` or `se/rd/serde`, as the index lays out crates.
+fn index_path(name: &str) -> PathBuf {
+    match name.len() {
+        1 => Path::new("1").join(name),
+        2 => Path::new("2").join(name),
+        3 => Path::new("3").join(&name[..1]).join(name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(name),
+    }
+}
+
+/// The JSON lines of a cache entry: a version byte, the index format as `u32` and a NUL terminated
+/// header, followed by NUL terminated pairs of version and JSON line.
+///
+/// A file cut off while cargo wrote it yields the complete entries before the cut.
+fn cache_entries(content: &[u8]) -> Vec<String> {
+    let Some(rest) = content.get(5..) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<&[u8]> = rest.split(|byte| *byte == 0).collect();
+    // whatever follows the last NUL is not terminated
+    fields.pop();
+    let mut fields = fields.into_iter().skip(1);
+    let mut res = Vec::new();
+    while let (Some(_version), Some(json)) = (fields.next(), fields.next()) {
+        res.push(String::from_utf8_lossy(json).into_owned());
+    }
+    res
+}
+
+/// Looks up the newer releases of every package, leaving out crates the index does not know.
+pub fn scan(index: &RegistryIndex, packages: &[RegistryPackage]) -> AllUpdates {
+    packages
+        .iter()
+        .filter_map(|package| {
+            let current = Version::parse(&package.version).ok()?;
+            let releases = index.releases(&package.name);
+            (!releases.is_empty()).then(|| (package.id.clone(), updates(&current, &releases)))
+        })
+        .collect()
+}
+
+/// The newest compatible and the newest release above `current`, pre-releases only for pre-releases.
+fn updates(current: &Version, releases: &[Release]) -> Updates {
+    let compatible = VersionReq::parse(&format!("^{}", current)).ok();
+    let is_compatible =
+        |version: &Version| compatible.as_ref().is_some_and(|req| req.matches(version));
+    let available = || {
+        releases
+            .iter()
+            .filter(|release| !release.yanked && release.version != *current)
+            .filter(|release| release.version.pre.is_empty() || !current.pre.is_empty())
+            .map(|release| &release.version)
+    };
+    let newer = || available().filter(|version| *version > current);
+    let yanked = releases
+        .iter()
+        .any(|release| release.yanked && release.version == *current);
+    Updates {
+        compatible: newer()
+            .filter(|version| is_compatible(version))
+            .max()
+            .cloned(),
+        latest: newer().max().cloned(),
+        yanked,
+        // an older release is compatible if its own `^` requirement allows the resolved version
+        replacement: yanked
+            .then(|| {
+                newer()
+                    .filter(|version| is_compatible(version))
+                    .min()
+                    .or_else(|| {
+                        available()
+                            .filter(|version| *version < current)
+                            .filter(|version| {
+                                VersionReq::parse(&format!("^{}", version))
+                                    .is_ok_and(|req| req.matches(current))
+                            })
+                            .max()
+                    })
+            })
+            .flatten()
+            .cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bump(from: &str, to: &str) -> Bump {
+        Bump::between(&Version::parse(from).unwrap(), &Version::parse(to).unwrap())
+    }
+
+    #[test]
+    fn bump_takes_the_leftmost_non_zero_component_as_major() {
+        assert_eq!(bump("1.2.3", "1.2.4"), Bump::Patch);
+        assert_eq!(bump("1.2.3", "1.3.0"), Bump::Minor);
+        assert_eq!(bump("1.2.3", "2.0.0"), Bump::Major);
+        assert_eq!(bump("0.3.1", "0.3.2"), Bump::Minor);
+        assert_eq!(bump("0.3.1", "0.4.0"), Bump::Major);
+        assert_eq!(bump("0.3.1", "1.0.0"), Bump::Major);
+        assert_eq!(bump("0.0.1", "0.0.2"), Bump::Major);
+        assert_eq!(bump("0.0.1", "0.1.0"), Bump::Major);
+    }
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    /// Releases of `versions`, those ending in `!` yanked.
+    fn releases(versions: &[&str]) -> Vec<Release> {
+        versions
+            .iter()
+            .map(|entry| Release {
+                version: version(entry.trim_end_matches('!')),
+                yanked: entry.ends_with('!'),
+            })
+            .collect()
+    }
+
+    fn updates_of(current: &str, versions: &[&str]) -> Updates {
+        updates(&version(current), &releases(versions))
+    }
+
+    #[test]
+    fn finds_compatible_and_latest_releases() {
+        let res = updates_of(
+            "1.0.1",
+            &["1.0.0", "1.0.1", "1.0.2", "1.1.0", "1.2.0!", "2.0.0"],
+        );
+        assert_eq!(
+            res,
+            Updates {
+                compatible: Some(version("1.1.0")),
+                latest: Some(version("2.0.0")),
+                yanked: false,
+                replacement: None,
+            }
+        );
+        assert_eq!(updates_of("2.0.0", &["1.0.0", "2.0.0"]), Updates::default());
+    }
+
+    #[test]
+    fn replaces_a_yanked_release_with_the_closest_newer_one() {
+        let res = updates_of(
+            "1.0.2",
+            &[
+                "1.0.0", "1.0.1", "1.0.2!", "1.0.3!", "1.0.4", "1.1.0", "2.0.0",
+            ],
+        );
+        assert!(res.yanked);
+        assert_eq!(res.replacement, Some(version("1.0.4")));
+        assert_eq!(res.compatible, Some(version("1.1.0")));
+        assert_eq!(res.latest, Some(version("2.0.0")));
+    }
+
+    #[test]
+    fn replaces_a_yanked_release_with_an_older_one_without_newer_ones() {
+        let res = updates_of("1.0.2", &["1.0.0", "1.0.1", "1.0.2!", "1.0.3!", "2.0.0"]);
+        assert!(res.yanked);
+        assert_eq!(res.replacement, Some(version("1.0.1")));
+        assert_eq!(res.compatible, None);
+
+        // `^0.3.9` does not allow 0.4.2
+        let res = updates_of("0.4.2", &["0.3.9", "0.4.1", "0.4.2!"]);
+        assert_eq!(res.replacement, Some(version("0.4.1")));
+        assert_eq!(updates_of("0.4.2", &["0.3.9", "0.4.2!"]).replacement, None);
+    }
+
+    #[test]
+    fn pre_releases_only_count_for_pre_releases() {
+        let res = updates_of("1.0.0", &["1.0.0", "1.0.1", "1.1.0-beta.1", "2.0.0-rc.1"]);
+        assert_eq!(res.compatible, Some(version("1.0.1")));
+        assert_eq!(res.latest, Some(version("1.0.1")));
+
+        let res = updates_of(
+            "2.0.0-alpha.1",
+            &["1.0.0", "2.0.0-alpha.1", "2.0.0-alpha.2"],
+        );
+        assert_eq!(res.compatible, Some(version("2.0.0-alpha.2")));
+        let res = updates_of(
+            "2.0.0-alpha.1",
+            &["2.0.0-alpha.1", "2.0.0-alpha.2", "2.0.0", "3.0.0-beta.1"],
+        );
+        assert_eq!(res.compatible, Some(version("2.0.0")));
+        assert_eq!(res.latest, Some(version("3.0.0-beta.1")));
+    }
+
+    /// A cache file with the header and an entry for every version.
+    fn cache_file(versions: &[&str]) -> Vec<u8> {
+        let mut res = vec![3, 2, 0, 0, 0];
+        res.extend(b"etag: \"abc\"\0");
+        for version in versions {
+            res.extend(format!("{}\0{{\"vers\":\"{}\"}}\0", version, version).bytes());
+        }
+        res
+    }
+
+    #[test]
+    fn reads_cache_entries() {
+        assert_eq!(
+            cache_entries(&cache_file(&["1.0.0", "1.0.1"])),
+            ["{\"vers\":\"1.0.0\"}", "{\"vers\":\"1.0.1\"}"]
+        );
+        assert!(cache_entries(&cache_file(&[])).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_complete_entries_of_a_truncated_cache_file() {
+        let content = cache_file(&["1.0.0", "1.0.1"]);
+        for cut in [content.len() - 1, content.len() - 5, content.len() - 14] {
+            assert_eq!(
+                cache_entries(&content[..cut]),
+                ["{\"vers\":\"1.0.0\"}"],
+                "cut at {}",
+                cut
+            );
+        }
+        assert!(cache_entries(&content[..10]).is_empty());
+        assert!(cache_entries(&content[..3]).is_empty());
+    }
+}
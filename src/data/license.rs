@@ -0,0 +1,538 @@
+use crate::error::{Errors, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The file a license policy is read from in the project directory, unless `--license-policy` names another.
+pub const POLICY_FILE: &str = "license-policy.toml";
+
+/// A license identifier with its `WITH` exception, `+` included in the identifier.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Term {
+    pub id: String,
+    pub exception: Option<String>,
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.exception {
+            Some(exception) => write!(f, "{} WITH {}", self.id, exception),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// A parsed SPDX license expression, `WITH` binding tighter than `AND` and `AND` tighter than `OR`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Expression {
+    License(Term),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+}
+
+impl Expression {
+    /// Parses an expression, taking the `/` of older manifests for `OR`.
+    pub fn parse(text: &str) -> std::result::Result<Expression, String> {
+        let tokens = tokenize(text);
+        let mut position = 0;
+        let res = parse_or(&tokens, &mut position)?;
+        match tokens.get(position) {
+            Some(token) => Err(format!("unexpected `{}`", token)),
+            None => Ok(res),
+        }
+    }
+
+    /// The same expression with nested operators flattened and operands sorted, for grouping licenses.
+    pub fn normalized(&self) -> Expression {
+        let flatten = |operands: &[Expression], is_and: bool| {
+            let mut res = Vec::new();
+            for operand in operands.iter().map(Expression::normalized) {
+                match operand {
+                    Expression::And(inner) if is_and => res.extend(inner),
+                    Expression::Or(inner) if !is_and => res.extend(inner),
+                    operand => res.push(operand),
+                }
+            }
+            res.sort();
+            res.dedup();
+            res
+        };
+        let single = |mut operands: Vec<Expression>,
+                      operator: fn(Vec<Expression>) -> Expression| {
+            if operands.len() == 1 {
+                operands.remove(0)
+            } else {
+                operator(operands)
+            }
+        };
+        match self {
+            Expression::License(term) => Expression::License(term.clone()),
+            Expression::And(operands) => single(flatten(operands, true), Expression::And),
+            Expression::Or(operands) => single(flatten(operands, false), Expression::Or),
+        }
+    }
+
+    /// Whether one of the choices the expression offers only takes licenses `accepts` agrees with.
+    pub fn satisfied_by(&self, accepts: &impl Fn(&Term) -> bool) -> bool {
+        match self {
+            Expression::License(term) => accepts(term),
+            Expression::And(operands) => {
+                operands.iter().all(|operand| operand.satisfied_by(accepts))
+            }
+            Expression::Or(operands) => {
+                operands.iter().any(|operand| operand.satisfied_by(accepts))
+            }
+        }
+    }
+
+    /// Every license the expression mentions.
+    pub fn terms(&self) -> Vec<&Term> {
+        match self {
+            Expression::License(term) => vec![term],
+            Expression::And(operands) | Expression::Or(operands) => {
+                operands.iter().flat_map(Expression::terms).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (operands, operator) = match self {
+            Expression::License(term) => return write!(f, "{}", term),
+            Expression::And(operands) => (operands, " AND "),
+            Expression::Or(operands) => (operands, " OR "),
+        };
+        for (index, operand) in operands.iter().enumerate() {
+            if index > 0 {
+                write!(f, "{}", operator)?;
+            }
+            match operand {
+                Expression::License(_) => write!(f, "{}", operand)?,
+                _ => write!(f, "({})", operand)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The license of a package in one spelling, `MIT/Apache-2.0` and `Apache-2.0 OR MIT` alike.
+///
+/// Packages without a license are grouped by whether they point to a license file.
+pub fn normalize(license: &str, license_file: &str) -> String {
+    match Expression::parse(license) {
+        Ok(expression) => expression.normalized().to_string(),
+        Err(_) if !license.trim().is_empty() => license.trim().to_string(),
+        Err(_) if !license_file.is_empty() => "(license file)".to_string(),
+        Err(_) => "(none)".to_string(),
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || matches!(c, '(' | ')' | '/') {
+            if !current.is_empty() {
+                res.push(std::mem::take(&mut current));
+            }
+            match c {
+                '/' => res.push("OR".to_string()),
+                '(' | ')' => res.push(c.to_string()),
+                _ => {}
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        res.push(current);
+    }
+    res
+}
+
+fn is_operator(token: &str, operator: &str) -> bool {
+    token.eq_ignore_ascii_case(operator)
+}
+
+fn parse_or(tokens: &[String], position: &mut usize) -> std::result::Result<Expression, String> {
+    let mut operands = vec![parse_and(tokens, position)?];
+    while tokens
+        .get(*position)
+        .is_some_and(|token| is_operator(token, "OR"))
+    {
+        *position += 1;
+        operands.push(parse_and(tokens, position)?);
+    }
+    Ok(if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        Expression::Or(operands)
+    })
+}
+
+fn parse_and(tokens: &[String], position: &mut usize) -> std::result::Result<Expression, String> {
+    let mut operands = vec![parse_license(tokens, position)?];
+    while tokens
+        .get(*position)
+        .is_some_and(|token| is_operator(token, "AND"))
+    {
+        *position += 1;
+        operands.push(parse_license(tokens, position)?);
+    }
+    Ok(if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        Expression::And(operands)
+    })
+}
+
+fn parse_license(
+    tokens: &[String],
+    position: &mut usize,
+) -> std::result::Result<Expression, String> {
+    let is_keyword = |token: &str| {
+        ["AND", "OR", "WITH", "(", ")"]
+            .iter()
+            .any(|keyword| is_operator(token, keyword))
+    };
+    match tokens.get(*position).map(String::as_str) {
+        Some("(") => {
+            *position += 1;
+            let res = parse_or(tokens, position)?;
+            match tokens.get(*position).map(String::as_str) {
+                Some(")") => {
+                    *position += 1;
+                    Ok(res)
+                }
+                _ => Err("missing `)`".to_string()),
+            }
+        }
+        Some(id) if !is_keyword(id) => {
+            *position += 1;
+            let mut term = Term {
+                id: id.to_string(),
+                exception: None,
+            };
+            if tokens
+                .get(*position)
+                .is_some_and(|token| is_operator(token, "WITH"))
+            {
+                match tokens.get(*position + 1) {
+                    Some(exception) if !is_keyword(exception) => {
+                        term.exception = Some(exception.clone())
+                    }
+                    _ => return Err("missing exception after `WITH`".to_string()),
+                }
+                *position += 2;
+            }
+            Ok(Expression::License(term))
+        }
+        Some(token) => Err(format!("unexpected `{}`", token)),
+        None => Err("missing license".to_string()),
+    }
+}
+
+/// Which licenses the crates in the tree may use.
+///
+/// ```toml
+/// allow = ["MIT", "Apache-2.0"]
+/// deny = ["GPL-3.0-only"]
+///
+/// [exceptions]
+/// ring = ["OpenSSL"]
+/// ```
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Policy {
+    /// The licenses crates may be used under, anything not denied if empty.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    /// Further licenses single crates may be used under, by crate name.
+    pub exceptions: BTreeMap<String, Vec<String>>,
+    /// Whether the workspace members are checked as well, which are usually not published.
+    pub check_workspace: bool,
+}
+
+impl Policy {
+    /// Reads the policy at `file`, or the [`POLICY_FILE`] in `project_path` if there is one.
+    pub fn load(project_path: &str, file: Option<&str>) -> Result<Option<Policy>> {
+        let path = match file {
+            Some(file) => Path::new(file).to_path_buf(),
+            None => Path::new(project_path).join(POLICY_FILE),
+        };
+        if !path.is_file() {
+            return match file {
+                Some(file) => Err(Errors::LicensePolicyNotFound(file.to_string())),
+                None => Ok(None),
+            };
+        }
+        let content = std::fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|e| Errors::ParseLicensePolicy(path.display().to_string(), e))
+    }
+
+    /// Whether the policy lists `term`, with or without its exception.
+    fn lists(list: &[String], term: &Term) -> bool {
+        list.iter()
+            .any(|license| *license == term.id || *license == term.to_string())
+    }
+
+    fn accepts(&self, name: &str, term: &Term) -> bool {
+        !Policy::lists(&self.deny, term)
+            && (self.allow.is_empty()
+                || Policy::lists(&self.allow, term)
+                || self
+                    .exceptions
+                    .get(name)
+                    .is_some_and(|exceptions| Policy::lists(exceptions, term)))
+    }
+}
+
+/// Why the license of a package needs a closer look.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseIssue {
+    /// The license breaks the policy.
+    Violation(String),
+    /// The license cannot be checked automatically.
+    Review(String),
+}
+
+impl LicenseIssue {
+    pub fn reason(&self) -> &str {
+        match self {
+            LicenseIssue::Violation(reason) | LicenseIssue::Review(reason) => reason,
+        }
+    }
+}
+
+/// Checks the license of the crate `name` against the policy.
+///
+/// Packages pointing to a license file instead of naming a license need a review even without a policy.
+/// Workspace members are only checked if the policy asks for it.
+pub fn check(
+    policy: Option<&Policy>,
+    name: &str,
+    license: &str,
+    license_file: &str,
+    member: bool,
+) -> Option<LicenseIssue> {
+    if member && !policy.is_some_and(|policy| policy.check_workspace) {
+        return None;
+    }
+    if license.trim().is_empty() {
+        return match (license_file.is_empty(), policy) {
+            (false, _) => Some(LicenseIssue::Review(format!(
+                "only a license file: {}",
+                license_file
+            ))),
+            (true, Some(_policy)) => Some(LicenseIssue::Review("no license given".to_string())),
+            (true, None) => None,
+        };
+    }
+    let expression = match Expression::parse(license) {
+        Ok(expression) => expression,
+        Err(e) => {
+            return Some(LicenseIssue::Review(format!(
+                "invalid SPDX expression: {}",
+                e
+            )));
+        }
+    };
+    let policy = policy?;
+    let accepts = |term: &Term| policy.accepts(name, term);
+    if expression.satisfied_by(&accepts) {
+        return None;
+    }
+    let reasons: Vec<String> = expression
+        .terms()
+        .into_iter()
+        .filter(|term| !accepts(term))
+        .map(|term| {
+            if Policy::lists(&policy.deny, term) {
+                format!("{} is denied", term)
+            } else {
+                format!("{} is not allowed", term)
+            }
+        })
+        .collect();
+    Some(LicenseIssue::Violation(reasons.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(id: &str) -> Expression {
+        Expression::License(Term {
+            id: id.to_string(),
+            exception: None,
+        })
+    }
+
+    fn policy(allow: &[&str], deny: &[&str]) -> Policy {
+        Policy {
+            allow: allow.iter().map(|license| license.to_string()).collect(),
+            deny: deny.iter().map(|license| license.to_string()).collect(),
+            ..Policy::default()
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Expression::parse("MIT OR Apache-2.0 AND BSD-3-Clause").unwrap(),
+            Expression::Or(vec![
+                license("MIT"),
+                Expression::And(vec![license("Apache-2.0"), license("BSD-3-Clause")])
+            ])
+        );
+        assert_eq!(
+            Expression::parse("(MIT OR Apache-2.0) AND BSD-3-Clause").unwrap(),
+            Expression::And(vec![
+                Expression::Or(vec![license("MIT"), license("Apache-2.0")]),
+                license("BSD-3-Clause")
+            ])
+        );
+    }
+
+    #[test]
+    fn with_binds_an_exception_to_its_license() {
+        let expression = Expression::parse("Apache-2.0 WITH LLVM-exception or MIT").unwrap();
+        assert_eq!(
+            expression,
+            Expression::Or(vec![
+                Expression::License(Term {
+                    id: "Apache-2.0".to_string(),
+                    exception: Some("LLVM-exception".to_string()),
+                }),
+                license("MIT"),
+            ])
+        );
+        assert_eq!(
+            expression.to_string(),
+            "Apache-2.0 WITH LLVM-exception OR MIT"
+        );
+    }
+
+    #[test]
+    fn slash_is_the_deprecated_or_and_plus_stays_in_the_id() {
+        assert_eq!(
+            Expression::parse("MIT/Apache-2.0").unwrap(),
+            Expression::Or(vec![license("MIT"), license("Apache-2.0")])
+        );
+        assert_eq!(Expression::parse("GPL-2.0+").unwrap(), license("GPL-2.0+"));
+        assert_eq!(
+            normalize("MIT/Apache-2.0", ""),
+            normalize("Apache-2.0 OR MIT", "")
+        );
+        assert_eq!(
+            normalize("(MIT OR (Apache-2.0 OR MIT))", ""),
+            "Apache-2.0 OR MIT"
+        );
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert!(Expression::parse("(MIT OR Apache-2.0").is_err());
+        assert!(Expression::parse("MIT AND").is_err());
+        assert!(Expression::parse("MIT WITH").is_err());
+        assert!(Expression::parse("MIT Apache-2.0").is_err());
+        assert!(Expression::parse("").is_err());
+    }
+
+    #[test]
+    fn one_allowed_choice_satisfies_the_policy() {
+        let policy = policy(&["MIT"], &[]);
+        assert_eq!(
+            check(Some(&policy), "a", "MIT OR GPL-3.0-only", "", false),
+            None
+        );
+        assert_eq!(
+            check(Some(&policy), "a", "MIT AND GPL-3.0-only", "", false),
+            Some(LicenseIssue::Violation(
+                "GPL-3.0-only is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn denied_licenses_and_exceptions() {
+        let mut policy = policy(&[], &["GPL-3.0-only", "Apache-2.0 WITH LLVM-exception"]);
+        assert_eq!(check(Some(&policy), "a", "BSD-3-Clause", "", false), None);
+        assert_eq!(
+            check(Some(&policy), "a", "GPL-3.0-only", "", false),
+            Some(LicenseIssue::Violation(
+                "GPL-3.0-only is denied".to_string()
+            ))
+        );
+        assert!(
+            check(
+                Some(&policy),
+                "a",
+                "Apache-2.0 WITH LLVM-exception",
+                "",
+                false
+            )
+            .is_some()
+        );
+        assert_eq!(check(Some(&policy), "a", "Apache-2.0", "", false), None);
+
+        policy.allow = vec!["MIT".to_string()];
+        policy
+            .exceptions
+            .insert("ring".to_string(), vec!["OpenSSL".to_string()]);
+        assert_eq!(
+            check(Some(&policy), "ring", "MIT AND OpenSSL", "", false),
+            None
+        );
+        assert!(check(Some(&policy), "other", "MIT AND OpenSSL", "", false).is_some());
+        assert_eq!(
+            check(Some(&policy), "a", "GPL-2.0+", "", false),
+            Some(LicenseIssue::Violation(
+                "GPL-2.0+ is not allowed".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_licenses_need_a_review() {
+        let policy = policy(&["MIT"], &[]);
+        assert_eq!(check(None, "a", "", "", false), None);
+        assert_eq!(
+            check(Some(&policy), "a", "", "", false),
+            Some(LicenseIssue::Review("no license given".to_string()))
+        );
+        assert!(matches!(
+            check(None, "a", "", "LICENSE", false),
+            Some(LicenseIssue::Review(_))
+        ));
+        assert!(matches!(
+            check(None, "a", "MIT OR", "", false),
+            Some(LicenseIssue::Review(_))
+        ));
+    }
+
+    #[test]
+    fn workspace_members_are_only_checked_on_request() {
+        let mut policy = policy(&["MIT"], &[]);
+        assert_eq!(check(Some(&policy), "member", "", "", true), None);
+        assert_eq!(
+            check(Some(&policy), "member", "GPL-3.0-only", "", true),
+            None
+        );
+        assert_eq!(check(None, "member", "", "LICENSE", true), None);
+
+        policy.check_workspace = true;
+        assert!(check(Some(&policy), "member", "", "", true).is_some());
+        assert!(check(Some(&policy), "member", "GPL-3.0-only", "", true).is_some());
+    }
+
+    #[test]
+    fn policy_files_use_kebab_case() {
+        let policy: Policy = toml::from_str("allow = [\"MIT\"]\ncheck-workspace = true\n").unwrap();
+        assert!(policy.check_workspace);
+        assert!(toml::from_str::<Policy>("alow = [\"MIT\"]\n").is_err());
+    }
+}
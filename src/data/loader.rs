@@ -0,0 +1,231 @@
+use crate::data::artifacts::{self, ArtifactPackage};
+use crate::data::index::{self, RegistryPackage};
+use crate::data::{
+    AllUpdates, ArtifactSizes, CargoMetadata, CodeStats, MetadataOptions, RegistryIndex, Size,
+    Timings, code, is_crates_io, size, timings,
+};
+use crate::error;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// What the background loader reports back to the UI thread.
+pub enum LoadEvent {
+    /// The resolved graph and how many sizes follow, sent first.
+    Metadata(Box<CargoMetadata>, usize),
+    /// The compiled artifacts in the target directory, sent right after the graph.
+    Artifacts(ArtifactSizes),
+    /// The compile times of the last `cargo build --timings`, sent right after the artifacts.
+    Timings(Timings),
+    /// The newer releases in the registry index, the last event before any size is measured.
+    Updates(AllUpdates),
+    /// The lines of code of one package, sent right before its size.
+    Code(String, CodeStats),
+    /// The size of one package.
+    Size(String, Size),
+    /// Cargo failed, the graph that follows is built from `Cargo.lock`.
+    Fallback(error::Errors),
+    Failed(error::Errors),
+}
+
+/// How far the background loader has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadProgress {
+    /// Waiting for `cargo metadata`.
+    Metadata,
+    /// Reading the target directory and the registry index, `total` sizes follow.
+    Scanning { total: usize },
+    /// Measuring the size of every package.
+    Sizes { done: usize, total: usize },
+}
+
+impl LoadProgress {
+    pub fn label(&self) -> String {
+        match self {
+            LoadProgress::Metadata => "Running cargo metadata...".to_string(),
+            LoadProgress::Scanning { .. } => {
+                "Reading build output and registry index...".to_string()
+            }
+            LoadProgress::Sizes { done, total } => {
+                format!("Measuring sizes: {} of {} packages", done, total)
+            }
+        }
+    }
+}
+
+/// Runs cargo metadata and measures the package sizes on a separate thread.
+///
+/// Dropping the loader cancels it, whatever the thread sends afterwards is discarded.
+pub struct Loader {
+    receiver: Receiver<LoadEvent>,
+    cancelled: Arc<AtomicBool>,
+    pub progress: LoadProgress,
+    /// The options the graph is loaded with.
+    pub options: MetadataOptions,
+}
+
+impl Loader {
+    /// Starts loading, packages in `known` already have a size and are not measured again.
+    ///
+    /// Newer releases are looked up in `index`.
+    pub fn spawn(
+        path: String,
+        options: MetadataOptions,
+        known: HashSet<String>,
+        index: RegistryIndex,
+    ) -> Loader {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let thread_cancelled = cancelled.clone();
+        let thread_options = options.clone();
+        thread::spawn(move || {
+            run(
+                &path,
+                &thread_options,
+                &known,
+                &index,
+                &sender,
+                &thread_cancelled,
+            )
+        });
+        Loader {
+            receiver,
+            cancelled,
+            progress: LoadProgress::Metadata,
+            options,
+        }
+    }
+
+    /// Everything sent since the last call, without blocking.
+    pub fn events(&mut self) -> Vec<LoadEvent> {
+        let events: Vec<LoadEvent> = self.receiver.try_iter().collect();
+        for event in &events {
+            if let LoadEvent::Fallback(_e) = event {
+                self.options.lockfile = true;
+            }
+            self.progress = match (self.progress, event) {
+                (_, LoadEvent::Metadata(_metadata, total)) => {
+                    LoadProgress::Scanning { total: *total }
+                }
+                // sizes are only counted once everything else about the graph has been delivered
+                (LoadProgress::Scanning { total }, LoadEvent::Updates(_updates)) => {
+                    LoadProgress::Sizes { done: 0, total }
+                }
+                (LoadProgress::Sizes { done, total }, LoadEvent::Size(..)) => LoadProgress::Sizes {
+                    done: done + 1,
+                    total,
+                },
+                (progress, _) => progress,
+            };
+        }
+        events
+    }
+
+    /// Whether every event about the graph has arrived and every size has been measured.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.progress, LoadProgress::Sizes { done, total } if done >= total)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn run(
+    path: &str,
+    options: &MetadataOptions,
+    known: &HashSet<String>,
+    index: &RegistryIndex,
+    sender: &Sender<LoadEvent>,
+    cancelled: &AtomicBool,
+) {
+    let metadata = match CargoMetadata::load(path, options) {
+        Ok(metadata) => metadata,
+        Err(e @ (error::Errors::RunCargoMetadata | error::Errors::CargoMetadataFailed(_)))
+            if Path::new(path).join("Cargo.lock").exists() =>
+        {
+            match CargoMetadata::from_lockfile(path) {
+                Ok(metadata) => {
+                    let _ = sender.send(LoadEvent::Fallback(e));
+                    metadata
+                }
+                Err(_lockfile_error) => {
+                    let _ = sender.send(LoadEvent::Failed(e));
+                    return;
+                }
+            }
+        }
+        Err(e) => {
+            let _ = sender.send(LoadEvent::Failed(e));
+            return;
+        }
+    };
+    let artifact_packages: Vec<ArtifactPackage> = metadata
+        .packages
+        .iter()
+        .map(|package| ArtifactPackage {
+            id: package.id.clone(),
+            name: package.name.clone(),
+            manifest_path: package.manifest_path.clone(),
+        })
+        .collect();
+    let target_directory = metadata.target_directory.clone();
+    let registry_packages: Vec<RegistryPackage> = metadata
+        .packages
+        .iter()
+        .filter(|package| package.source.as_deref().is_some_and(is_crates_io))
+        .map(|package| RegistryPackage {
+            id: package.id.clone(),
+            name: package.name.clone(),
+            version: package.version.clone(),
+        })
+        .collect();
+    let timings = timings::scan(&target_directory, &metadata.packages);
+    let packages: Vec<(String, String)> = metadata
+        .packages
+        .iter()
+        .filter(|package| !known.contains(&package.id))
+        .map(|package| (package.id.clone(), package.manifest_path.clone()))
+        .collect();
+    if sender
+        .send(LoadEvent::Metadata(Box::new(metadata), packages.len()))
+        .is_err()
+    {
+        return;
+    }
+    let artifacts = artifacts::scan(&target_directory, &artifact_packages);
+    if sender.send(LoadEvent::Artifacts(artifacts)).is_err()
+        || sender.send(LoadEvent::Timings(timings)).is_err()
+    {
+        return;
+    }
+    if sender
+        .send(LoadEvent::Updates(index::scan(index, &registry_packages)))
+        .is_err()
+    {
+        return;
+    }
+    for (id, manifest_path) in packages {
+        if cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(stats) = code::count(&manifest_path)
+            && sender.send(LoadEvent::Code(id.clone(), stats)).is_err()
+        {
+            return;
+        }
+        let size = size::measure(&manifest_path);
+        if sender.send(LoadEvent::Size(id, size)).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,581 @@
+use crate::data::cargo::{METADATA_VERSION, cargo_home};
+use crate::data::{CargoMetadata, DepKindInfo, DependencyKind, Node, NodeDep, Package, Resolve};
+use crate::error;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// `Cargo.lock` in any format from v1 to v4.
+#[derive(Debug, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockPackage>,
+    /// The root package of v1 lockfiles written by old cargo versions.
+    root: Option<LockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    /// `name`, `name version` or `name version (source)`, as short as still unambiguous.
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl LockPackage {
+    fn id(&self) -> String {
+        format!(
+            "{}#{}@{}",
+            self.source.as_deref().unwrap_or("path+file://"),
+            self.name,
+            self.version
+        )
+    }
+
+    /// Whether the dependency entry of another package points to this one.
+    fn matches(&self, entry: &str) -> bool {
+        let mut parts = entry.splitn(3, ' ');
+        parts.next() == Some(self.name.as_str())
+            && parts.next().is_none_or(|version| version == self.version)
+            && parts.next().is_none_or(|source| {
+                let source = source.trim_start_matches('(').trim_end_matches(')');
+                self.source.as_deref() == Some(source)
+            })
+    }
+}
+
+impl CargoMetadata {
+    /// Builds the graph from the `Cargo.lock` inside `path` alone, without invoking cargo.
+    ///
+    /// If `path` is a workspace member, the lockfile of the workspace root above it is used,
+    /// and the members are the ones the root manifest lists.
+    ///
+    /// The lockfile knows no dependency kinds, platforms or features, so every edge is a
+    /// normal, unconditional one. Registry and git packages are looked up in the local cargo
+    /// cache, or in `vendor` of the project, for their manifest and size.
+    pub fn from_lockfile(path: &str) -> error::Result<CargoMetadata> {
+        let Some(root) = workspace_root(Path::new(path)) else {
+            return Err(error::Errors::CargoLockNotFound);
+        };
+        let lockfile: Lockfile =
+            toml::from_str(&std::fs::read_to_string(root.join("Cargo.lock"))?)?;
+        let packages: Vec<LockPackage> =
+            lockfile.package.into_iter().chain(lockfile.root).collect();
+        let mut by_name: HashMap<&str, Vec<&LockPackage>> = HashMap::new();
+        for package in &packages {
+            by_name
+                .entry(package.name.as_str())
+                .or_default()
+                .push(package);
+        }
+
+        let registries = registry_dirs();
+        let root_path = root.to_string_lossy().to_string();
+        let members = Members::of(&root);
+        let mut workspace_members = Vec::new();
+        let mut workspace_default_members = Vec::new();
+        let mut nodes = Vec::new();
+        let mut res_packages = Vec::new();
+
+        for package in &packages {
+            let id = package.id();
+            let member = members
+                .manifests
+                .get(&package.name)
+                .filter(|_| package.source.is_none());
+            let manifest_path = match &package.source {
+                None => member.cloned(),
+                Some(source)
+                    if source.starts_with("registry+") || source.starts_with("sparse+") =>
+                {
+                    registry_manifest(&registries, &package.name, &package.version)
+                        .or_else(|| vendored_manifest(&root, &package.name, &package.version))
+                }
+                Some(source) if source.starts_with("git+") => git_manifest(source, &package.name)
+                    .or_else(|| vendored_manifest(&root, &package.name, &package.version)),
+                Some(_) => None,
+            };
+            if member.is_some() {
+                workspace_members.push(id.clone());
+                if members.defaults.contains(&package.name) {
+                    workspace_default_members.push(id.clone());
+                }
+            }
+
+            let deps = package
+                .dependencies
+                .iter()
+                .filter_map(|entry| {
+                    let name = entry.split(' ').next().unwrap_or_default();
+                    let dependency = by_name
+                        .get(name)?
+                        .iter()
+                        .find(|candidate| candidate.matches(entry))?;
+                    Some(NodeDep {
+                        name: dependency.name.replace('-', "_"),
+                        pkg: dependency.id(),
+                        dep_kinds: vec![DepKindInfo {
+                            kind: DependencyKind::Normal,
+                            target: None,
+                        }],
+                    })
+                })
+                .collect::<Vec<_>>();
+            nodes.push(Node {
+                id: id.clone(),
+                dependencies: deps.iter().map(|dep| dep.pkg.clone()).collect(),
+                deps,
+                features: Vec::new(),
+            });
+
+            let manifest = manifest_path
+                .as_deref()
+                .and_then(manifest_table)
+                .unwrap_or_default();
+            let field = |name: &str| {
+                manifest
+                    .get("package")
+                    .and_then(|package| package.get(name))
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string)
+            };
+            res_packages.push(Package {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                id,
+                license: field("license"),
+                license_file: field("license-file"),
+                description: field("description"),
+                documentation: field("documentation"),
+                repository: field("repository"),
+                source: package.source.clone(),
+                manifest_path: manifest_path
+                    .map(|manifest_path| manifest_path.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                dependencies: Vec::new(),
+                targets: Vec::new(),
+                features: manifest
+                    .get("features")
+                    .cloned()
+                    .and_then(|features| features.try_into().ok())
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(CargoMetadata {
+            packages: res_packages,
+            workspace_members,
+            workspace_default_members,
+            resolve: Some(Resolve { nodes, root: None }),
+            target_directory: root.join("target").to_string_lossy().to_string(),
+            workspace_root: root_path,
+            version: METADATA_VERSION,
+        })
+    }
+}
+
+/// The directory of the `Cargo.lock` that `path` uses, which is the workspace root if `path` is a member.
+fn workspace_root(path: &Path) -> Option<PathBuf> {
+    if path.join("Cargo.lock").exists() {
+        return Some(path.to_path_buf());
+    }
+    let path = std::fs::canonicalize(path).ok()?;
+    path.ancestors()
+        .skip(1)
+        .find(|dir| {
+            dir.join("Cargo.lock").exists()
+                && manifest_table(&dir.join("Cargo.toml"))
+                    .is_some_and(|manifest| manifest.contains_key("workspace"))
+        })
+        .map(Path::to_path_buf)
+}
+
+/// The packages of a workspace as its root manifest lists them.
+#[derive(Debug, Default)]
+struct Members {
+    /// Manifest of every member by package name.
+    manifests: HashMap<String, PathBuf>,
+    /// Names of the members cargo builds when none is selected.
+    defaults: BTreeSet<String>,
+}
+
+impl Members {
+    /// Reads `[workspace] members`, `exclude` and `default-members` of the manifest in `root`.
+    ///
+    /// Without `default-members` the root package is the default one, or every member in a virtual workspace.
+    fn of(root: &Path) -> Members {
+        let Some(manifest) = manifest_table(&root.join("Cargo.toml")) else {
+            return Members::default();
+        };
+        let workspace = manifest.get("workspace");
+        let patterns = |key: &str| -> Vec<PathBuf> {
+            workspace
+                .and_then(|workspace| workspace.get(key)?.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|pattern| pattern.as_str())
+                .flat_map(|pattern| expand(root, pattern))
+                .collect()
+        };
+        let excluded = patterns("exclude");
+        let mut dirs: Vec<PathBuf> = patterns("members")
+            .into_iter()
+            .filter(|dir| !excluded.contains(dir))
+            .collect();
+        let root_package = package_name(&root.join("Cargo.toml"));
+        if root_package.is_some() {
+            dirs.push(root.to_path_buf());
+        }
+
+        let mut res = Members::default();
+        for dir in dirs {
+            let manifest_path = dir.join("Cargo.toml");
+            if let Some(name) = package_name(&manifest_path) {
+                res.manifests.insert(name, manifest_path);
+            }
+        }
+        res.defaults = match workspace.and_then(|workspace| workspace.get("default-members")) {
+            Some(_) => patterns("default-members")
+                .iter()
+                .filter_map(|dir| package_name(&dir.join("Cargo.toml")))
+                .collect(),
+            None => match root_package {
+                Some(name) => BTreeSet::from([name]),
+                None => res.manifests.keys().cloned().collect(),
+            },
+        };
+        res
+    }
+}
+
+/// The `package.name` of the manifest at `path`.
+fn package_name(path: &Path) -> Option<String> {
+    manifest_table(path)?
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// The directories below `root` matching a workspace member pattern such as `crates/*`.
+fn expand(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for component in pattern
+        .split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+    {
+        if !component.contains(['*', '?']) {
+            dirs = dirs.into_iter().map(|dir| dir.join(component)).collect();
+            continue;
+        }
+        let mut matching = Vec::new();
+        for dir in dirs {
+            for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+                if entry.path().is_dir()
+                    && wildcard(component, &entry.file_name().to_string_lossy())
+                {
+                    matching.push(entry.path());
+                }
+            }
+        }
+        matching.sort();
+        dirs = matching;
+    }
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any text and `?` for any character.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len())
+            .filter(|at| name.is_char_boundary(*at))
+            .any(|at| wildcard(&pattern[1..], &name[at..])),
+        Some(first) => {
+            let mut rest = name.chars();
+            rest.next()
+                .is_some_and(|next| first == '?' || first == next)
+                && wildcard(&pattern[first.len_utf8()..], rest.as_str())
+        }
+    }
+}
+
+/// The parsed manifest at `path`, if it exists and is valid.
+fn manifest_table(path: &Path) -> Option<toml::Table> {
+    toml::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// The `<index>-<hash>` directory names below `registry/src` and `registry/cache`.
+fn registry_dirs() -> Vec<String> {
+    let Some(home) = cargo_home() else {
+        return Vec::new();
+    };
+    let mut res = BTreeSet::new();
+    for dir in ["src", "cache"] {
+        if let Ok(entries) = std::fs::read_dir(home.join("registry").join(dir)) {
+            for entry in entries.flatten() {
+                res.insert(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    res.into_iter().collect()
+}
+
+/// Where the manifest of an extracted registry package is or would be.
+///
+/// The package only has to be present in one of `registry/src` and `registry/cache`,
+/// the size lookup maps the manifest path to the `.crate` archive.
+fn registry_manifest(registries: &[String], name: &str, version: &str) -> Option<PathBuf> {
+    let registry = cargo_home()?.join("registry");
+    let dir_name = format!("{}-{}", name, version);
+    registries
+        .iter()
+        .find(|index| {
+            registry.join("src").join(index).join(&dir_name).exists()
+                || registry
+                    .join("cache")
+                    .join(index)
+                    .join(format!("{}.crate", dir_name))
+                    .exists()
+        })
+        .map(|index| {
+            registry
+                .join("src")
+                .join(index)
+                .join(dir_name)
+                .join("Cargo.toml")
+        })
+}
+
+/// The manifest of a vendored package, `vendor/<name>-<version>` or `vendor/<name>` as `cargo vendor` writes them.
+fn vendored_manifest(root: &Path, name: &str, version: &str) -> Option<PathBuf> {
+    let vendor = root.join("vendor");
+    [
+        vendor.join(format!("{}-{}", name, version)),
+        vendor.join(name),
+    ]
+    .into_iter()
+    .map(|dir| dir.join("Cargo.toml"))
+    .find(|manifest| manifest.exists())
+}
+
+/// The manifest of a package in a git checkout below `git/checkouts/<repository>-<hash>/<short revision>`.
+///
+/// A repository can hold several packages, the one called `name` is searched a few levels deep.
+fn git_manifest(source: &str, name: &str) -> Option<PathBuf> {
+    let revision = source.rsplit_once('#')?.1;
+    let short_revision = revision.get(..7)?;
+    let checkouts = std::fs::read_dir(cargo_home()?.join("git").join("checkouts")).ok()?;
+    checkouts
+        .flatten()
+        .map(|repository| repository.path().join(short_revision))
+        .filter(|checkout| checkout.is_dir())
+        .find_map(|checkout| {
+            let mut stack = vec![(checkout, 0)];
+            while let Some((dir, depth)) = stack.pop() {
+                let manifest = dir.join("Cargo.toml");
+                if package_name(&manifest).as_deref() == Some(name) {
+                    return Some(manifest);
+                }
+                if depth < 3
+                    && let Ok(entries) = std::fs::read_dir(&dir)
+                {
+                    for entry in entries.flatten() {
+                        if entry.path().is_dir() && entry.file_name() != ".git" {
+                            stack.push((entry.path(), depth + 1));
+                        }
+                    }
+                }
+            }
+            None
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::testing::TempDir;
+
+    const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+    fn load(dir: &Path) -> CargoMetadata {
+        CargoMetadata::from_lockfile(&dir.to_string_lossy()).unwrap()
+    }
+
+    fn registry(name: &str, version: &str) -> String {
+        format!("{}#{}@{}", REGISTRY, name, version)
+    }
+
+    fn local(name: &str) -> String {
+        format!("path+file://#{}@0.1.0", name)
+    }
+
+    /// The ids of the packages the one with `id` depends on.
+    fn dependencies(metadata: &CargoMetadata, id: &str) -> Vec<String> {
+        let node = metadata
+            .resolve
+            .as_ref()
+            .unwrap()
+            .nodes
+            .iter()
+            .find(|node| node.id == id)
+            .unwrap();
+        node.deps.iter().map(|dep| dep.pkg.clone()).collect()
+    }
+
+    #[test]
+    fn parses_v1_lockfiles() {
+        let lockfile = format!(
+            "[root]\nname = \"app\"\nversion = \"0.1.0\"\n\
+             dependencies = [\n \"log 0.3.9 ({0})\",\n \"log 0.4.0 ({0})\",\n]\n\n\
+             [[package]]\nname = \"log\"\nversion = \"0.3.9\"\nsource = \"{0}\"\ndependencies = [\n \"log 0.4.0 ({0})\",\n]\n\n\
+             [[package]]\nname = \"log\"\nversion = \"0.4.0\"\nsource = \"{0}\"\n\n\
+             [metadata]\n\"checksum log 0.3.9 ({0})\" = \"abc\"\n",
+            REGISTRY
+        );
+        let dir = TempDir::new(
+            "lockfile-v1",
+            &[
+                (
+                    "Cargo.toml",
+                    "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+                ),
+                ("Cargo.lock", &lockfile),
+            ],
+        );
+        let metadata = load(dir.path());
+        assert_eq!(metadata.workspace_members, [local("app")]);
+        assert_eq!(metadata.workspace_default_members, [local("app")]);
+        assert_eq!(
+            dependencies(&metadata, &local("app")),
+            [registry("log", "0.3.9"), registry("log", "0.4.0")]
+        );
+        assert_eq!(
+            dependencies(&metadata, &registry("log", "0.3.9")),
+            [registry("log", "0.4.0")]
+        );
+    }
+
+    #[test]
+    fn parses_v2_to_v4_lockfiles() {
+        for version in [None, Some(3), Some(4)] {
+            let lockfile = format!(
+                "{}[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\n \"log\",\n \"serde 0.9.0\",\n \"serde 1.0.0\",\n]\n\n\
+                 [[package]]\nname = \"log\"\nversion = \"0.4.0\"\nsource = \"{1}\"\nchecksum = \"abc\"\n\n\
+                 [[package]]\nname = \"serde\"\nversion = \"0.9.0\"\nsource = \"{1}\"\n\n\
+                 [[package]]\nname = \"serde\"\nversion = \"1.0.0\"\nsource = \"{1}\"\ndependencies = [\"log\"]\n",
+                version
+                    .map(|version| format!("version = {}\n\n", version))
+                    .unwrap_or_default(),
+                REGISTRY
+            );
+            let dir = TempDir::new(
+                &format!("lockfile-v{}", version.unwrap_or(2)),
+                &[
+                    (
+                        "Cargo.toml",
+                        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+                    ),
+                    ("Cargo.lock", &lockfile),
+                ],
+            );
+            let metadata = load(dir.path());
+            assert_eq!(metadata.workspace_members, [local("app")]);
+            assert_eq!(
+                dependencies(&metadata, &local("app")),
+                [
+                    registry("log", "0.4.0"),
+                    registry("serde", "0.9.0"),
+                    registry("serde", "1.0.0")
+                ]
+            );
+            assert_eq!(
+                dependencies(&metadata, &registry("serde", "1.0.0")),
+                [registry("log", "0.4.0")]
+            );
+        }
+    }
+
+    #[test]
+    fn members_come_from_the_workspace_root() {
+        let lockfile = "version = 4\n\n\
+             [[package]]\nname = \"a\"\nversion = \"0.1.0\"\ndependencies = [\"b\", \"helper\"]\n\n\
+             [[package]]\nname = \"b\"\nversion = \"0.1.0\"\n\n\
+             [[package]]\nname = \"helper\"\nversion = \"0.1.0\"\n\n\
+             [[package]]\nname = \"skipped\"\nversion = \"0.1.0\"\n";
+        let package = |name: &str| format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name);
+        let dir = TempDir::new(
+            "lockfile-workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/skipped\"]\n",
+                ),
+                ("Cargo.lock", lockfile),
+                ("crates/a/Cargo.toml", &package("a")),
+                ("crates/b/Cargo.toml", &package("b")),
+                ("crates/skipped/Cargo.toml", &package("skipped")),
+                ("helper/Cargo.toml", &package("helper")),
+            ],
+        );
+        let metadata = load(&dir.path().join("crates").join("a"));
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(metadata.workspace_root, root.to_string_lossy());
+        assert_eq!(metadata.workspace_members, [local("a"), local("b")]);
+        assert_eq!(metadata.workspace_default_members, [local("a"), local("b")]);
+        let a = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == "a")
+            .unwrap();
+        assert_eq!(
+            a.manifest_path,
+            root.join("crates/a/Cargo.toml").to_string_lossy()
+        );
+        let helper = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == "helper")
+            .unwrap();
+        assert_eq!(helper.manifest_path, "");
+    }
+
+    #[test]
+    fn default_members_are_taken_from_the_root_manifest() {
+        let dir = TempDir::new(
+            "lockfile-default-members",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\"a\", \"./b\"]\ndefault-members = [\"b\"]\n",
+                ),
+                (
+                    "Cargo.lock",
+                    "version = 3\n\n[[package]]\nname = \"a\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"b\"\nversion = \"0.1.0\"\n",
+                ),
+                (
+                    "a/Cargo.toml",
+                    "[package]\nname = \"a\"\nversion = \"0.1.0\"\n",
+                ),
+                (
+                    "b/Cargo.toml",
+                    "[package]\nname = \"b\"\nversion.workspace = true\n",
+                ),
+            ],
+        );
+        let metadata = load(dir.path());
+        assert_eq!(metadata.workspace_members, [local("a"), local("b")]);
+        assert_eq!(metadata.workspace_default_members, [local("b")]);
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard("*", "anything"));
+        assert!(wildcard("crate-*", "crate-a"));
+        assert!(wildcard("?-*-x", "a-bc-x"));
+        assert!(!wildcard("crate-*", "other"));
+        assert!(!wildcard("a?", "a"));
+    }
+}
@@ -0,0 +1,446 @@
+use crate::data::DependencyKind;
+use crate::error::{Errors, Result};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// How many unchanged lines the diff keeps around each change.
+const DIFF_CONTEXT: usize = 2;
+
+/// Where a dependency is declared: its kind and the `cfg(..)` or target triple of its `[target]` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    pub kind: DependencyKind,
+    pub platform: Option<String>,
+}
+
+/// A line of the diff between a manifest and its edited version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Removed(String),
+    Added(String),
+    /// The number of unchanged lines left out.
+    Skipped(usize),
+}
+
+/// A dependency removed from the manifest of a workspace member, written once confirmed.
+#[derive(Debug, Clone)]
+pub struct Removal {
+    /// The name of the removed crate.
+    pub name: String,
+    /// The name of the member it is removed from.
+    pub member: String,
+    pub manifest_path: String,
+    /// The tables the crate is removed from, like `dev-dependencies` or `target.'cfg(unix)'.dependencies`.
+    pub tables: Vec<String>,
+    pub diff: Vec<DiffLine>,
+    original: String,
+    edited: String,
+}
+
+impl Removal {
+    /// Removes the crate `name` from the tables of the manifest at `manifest_path` it is declared in according to
+    /// `declarations`, along with the features referring to it once nothing else declares it.
+    ///
+    /// Formatting and comments of the rest of the manifest are kept.
+    pub fn plan(
+        manifest_path: &str,
+        member: &str,
+        name: &str,
+        declarations: &[Declaration],
+    ) -> Result<Removal> {
+        let original = std::fs::read_to_string(manifest_path)?;
+        let mut document: DocumentMut = original
+            .parse()
+            .map_err(|e| Errors::ParseManifest(manifest_path.to_string(), e))?;
+        let mut tables = Vec::new();
+        let mut keys = Vec::new();
+        for kind in DependencyKind::ALL {
+            for section in sections(kind) {
+                if declarations.contains(&Declaration {
+                    kind,
+                    platform: None,
+                }) && let Some(dependencies) =
+                    document.get_mut(section).and_then(Item::as_table_like_mut)
+                {
+                    let removed = remove_crate(dependencies, name);
+                    if !removed.is_empty() {
+                        tables.push(section.to_string());
+                        keys.extend(removed);
+                    }
+                }
+                let Some(targets) = document.get_mut("target").and_then(Item::as_table_like_mut)
+                else {
+                    continue;
+                };
+                for (platform, target) in targets.iter_mut() {
+                    let declared = declarations.iter().any(|declaration| {
+                        declaration.kind == kind
+                            && declaration
+                                .platform
+                                .as_deref()
+                                .is_some_and(|declared| same_platform(declared, platform.get()))
+                    });
+                    if declared
+                        && let Some(dependencies) =
+                            target.get_mut(section).and_then(Item::as_table_like_mut)
+                    {
+                        let removed = remove_crate(dependencies, name);
+                        if !removed.is_empty() {
+                            tables.push(format!("target.'{}'.{}", platform.get(), section));
+                            keys.extend(removed);
+                        }
+                    }
+                }
+            }
+        }
+        if tables.is_empty() {
+            return Err(Errors::DependencyNotDeclared(
+                name.to_string(),
+                manifest_path.to_string(),
+            ));
+        }
+
+        // features can only refer to normal and build dependencies, which may still be declared for another target
+        keys.retain(|key| !declares(&document, key));
+        if let Some(features) = document
+            .get_mut("features")
+            .and_then(Item::as_table_like_mut)
+        {
+            // a bare name enables an optional dependency only if there is no feature of that name
+            let implicit: Vec<bool> = keys.iter().map(|key| !features.contains_key(key)).collect();
+            for (_feature, enabled) in features.iter_mut() {
+                if let Some(enabled) = enabled.as_array_mut() {
+                    let refers = |value: &Value| {
+                        value.as_str().is_some_and(|value| {
+                            keys.iter()
+                                .zip(&implicit)
+                                .any(|(key, implicit)| refers_to(value, key, *implicit))
+                        })
+                    };
+                    let first_removed = enabled.get(0).is_some_and(refers);
+                    enabled.retain(|value| !refers(value));
+                    // the new first entry would keep the space that separated it from the removed one
+                    if first_removed && let Some(first) = enabled.get_mut(0) {
+                        first.decor_mut().set_prefix("");
+                    }
+                }
+            }
+        }
+
+        let edited = document.to_string();
+        Ok(Removal {
+            name: name.to_string(),
+            member: member.to_string(),
+            manifest_path: manifest_path.to_string(),
+            tables,
+            diff: diff(&original, &edited),
+            original,
+            edited,
+        })
+    }
+
+    /// Writes the edited manifest.
+    pub fn apply(&self) -> Result<()> {
+        std::fs::write(&self.manifest_path, &self.edited)?;
+        Ok(())
+    }
+
+    /// Writes the manifest as it was, unless it changed since the edit.
+    pub fn revert(&self) -> Result<()> {
+        if std::fs::read_to_string(&self.manifest_path)? != self.edited {
+            return Err(Errors::ManifestChanged(self.manifest_path.clone()));
+        }
+        std::fs::write(&self.manifest_path, &self.original)?;
+        Ok(())
+    }
+}
+
+/// The tables declaring dependencies of `kind`, with the spelling of older manifests.
+fn sections(kind: DependencyKind) -> &'static [&'static str] {
+    match kind {
+        DependencyKind::Normal => &["dependencies"],
+        DependencyKind::Development => &["dev-dependencies", "dev_dependencies"],
+        DependencyKind::Build => &["build-dependencies", "build_dependencies"],
+    }
+}
+
+/// Removes the entries of the crate `name`, renamed ones included, and returns their keys.
+fn remove_crate(dependencies: &mut dyn TableLike, name: &str) -> Vec<String> {
+    let keys: Vec<String> = dependencies
+        .iter()
+        .filter(|(key, item)| item.get("package").and_then(Item::as_str).unwrap_or(key) == name)
+        .map(|(key, _item)| key.to_string())
+        .collect();
+    for key in &keys {
+        dependencies.remove(key);
+    }
+    keys
+}
+
+/// Whether a normal or build dependency is still declared under `key`, for any target.
+fn declares(document: &DocumentMut, key: &str) -> bool {
+    let declared_in = |table: &dyn TableLike| {
+        [DependencyKind::Normal, DependencyKind::Build]
+            .iter()
+            .flat_map(|kind| sections(*kind))
+            .any(|section| {
+                table
+                    .get(section)
+                    .and_then(Item::as_table_like)
+                    .is_some_and(|table| table.contains_key(key))
+            })
+    };
+    declared_in(document.as_table())
+        || document
+            .get("target")
+            .and_then(Item::as_table_like)
+            .is_some_and(|targets| {
+                targets
+                    .iter()
+                    .any(|(_platform, target)| target.as_table_like().is_some_and(declared_in))
+            })
+}
+
+/// Whether the feature value `value` enables the dependency `key` or one of its features.
+fn refers_to(value: &str, key: &str, implicit: bool) -> bool {
+    let dependency = value
+        .split_once('/')
+        .map_or(value, |(dependency, _feature)| dependency);
+    value.strip_prefix("dep:") == Some(key)
+        || (value.contains('/') && dependency.trim_end_matches('?') == key)
+        || (implicit && value == key)
+}
+
+/// Cargo prints `cfg(..)` expressions in its own spacing, which need not be the manifest's.
+fn same_platform(a: &str, b: &str) -> bool {
+    a.replace(' ', "") == b.replace(' ', "")
+}
+
+/// The line diff of two texts, unchanged lines far from any change left out.
+fn diff(original: &str, edited: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = edited.lines().collect();
+    // the length of the longest common subsequence of the lines from `i` and `j` on
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<bool> = lines
+        .iter()
+        .map(|line| !matches!(line, DiffLine::Unchanged(_)))
+        .collect();
+    let mut res = Vec::new();
+    let mut skipped = 0;
+    for (position, line) in lines.into_iter().enumerate() {
+        let near = changed[position.saturating_sub(DIFF_CONTEXT)
+            ..(position + DIFF_CONTEXT + 1).min(changed.len())]
+            .iter()
+            .any(|changed| *changed);
+        if !near {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            res.push(DiffLine::Skipped(std::mem::take(&mut skipped)));
+        }
+        res.push(line);
+    }
+    if skipped > 0 {
+        res.push(DiffLine::Skipped(skipped));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::testing::TempDir;
+
+    /// The path of the manifest in `dir`.
+    fn manifest(dir: &TempDir) -> String {
+        dir.path().join("Cargo.toml").to_string_lossy().to_string()
+    }
+
+    fn declared(kind: DependencyKind, platform: Option<&str>) -> Declaration {
+        Declaration {
+            kind,
+            platform: platform.map(str::to_string),
+        }
+    }
+
+    fn plan(test: &str, content: &str, name: &str, declarations: &[Declaration]) -> Removal {
+        let dir = TempDir::new(test, &[("Cargo.toml", content)]);
+        Removal::plan(&manifest(&dir), "member", name, declarations).unwrap()
+    }
+
+    #[test]
+    fn removes_renamed_entries_and_keeps_formatting() {
+        let removal = plan(
+            "renamed",
+            "[dependencies]\n# logging\nlogging = { package = \"log\", version = \"0.4\" }\nserde = \"1\"  # kept\n",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        );
+        assert_eq!(removal.tables, ["dependencies"]);
+        assert_eq!(removal.edited, "[dependencies]\nserde = \"1\"  # kept\n");
+    }
+
+    #[test]
+    fn removes_from_the_declared_target_only() {
+        let removal = plan(
+            "target",
+            "[target.'cfg(target_os=\"linux\")'.dependencies]\nlog = \"0.4\"\n\n[target.'cfg(windows)'.dependencies]\nlog = \"0.4\"\n",
+            "log",
+            &[declared(
+                DependencyKind::Normal,
+                Some("cfg(target_os = \"linux\")"),
+            )],
+        );
+        assert_eq!(
+            removal.tables,
+            ["target.'cfg(target_os=\"linux\")'.dependencies"]
+        );
+        assert_eq!(
+            removal.edited,
+            "[target.'cfg(target_os=\"linux\")'.dependencies]\n\n[target.'cfg(windows)'.dependencies]\nlog = \"0.4\"\n"
+        );
+    }
+
+    #[test]
+    fn removes_from_the_legacy_spelling_of_the_kind_only() {
+        let removal = plan(
+            "legacy",
+            "[dependencies]\nlog = \"0.4\"\n\n[dev_dependencies]\nlog = \"0.4\"\n",
+            "log",
+            &[declared(DependencyKind::Development, None)],
+        );
+        assert_eq!(removal.tables, ["dev_dependencies"]);
+        assert_eq!(
+            removal.edited,
+            "[dependencies]\nlog = \"0.4\"\n\n[dev_dependencies]\n"
+        );
+    }
+
+    #[test]
+    fn drops_feature_entries_enabling_the_dependency() {
+        let removal = plan(
+            "features",
+            "[features]\ndefault = [\"log\"]\nstd = [\"dep:log\", \"log/std\", \"log?/kv\", \"serde/std\"]\n\n\
+             [dependencies]\nlog = { version = \"0.4\", optional = true }\nserde = \"1\"\n",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        );
+        assert_eq!(
+            removal.edited,
+            "[features]\ndefault = []\nstd = [\"serde/std\"]\n\n[dependencies]\nserde = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn keeps_a_feature_named_like_the_dependency() {
+        let removal = plan(
+            "feature-name",
+            "[features]\ndefault = [\"log\"]\nlog = [\"dep:log\"]\n\n[dependencies]\nlog = { version = \"0.4\", optional = true }\n",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        );
+        assert_eq!(
+            removal.edited,
+            "[features]\ndefault = [\"log\"]\nlog = []\n\n[dependencies]\n"
+        );
+    }
+
+    #[test]
+    fn keeps_features_while_another_target_declares_the_dependency() {
+        let removal = plan(
+            "still-declared",
+            "[features]\nstd = [\"log/std\"]\n\n[dependencies]\nlog = \"0.4\"\n\n[target.'cfg(unix)'.dependencies]\nlog = \"0.4\"\n",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        );
+        assert!(removal.edited.contains("std = [\"log/std\"]"));
+    }
+
+    #[test]
+    fn fails_for_crates_not_declared() {
+        let dir = TempDir::new(
+            "undeclared",
+            &[("Cargo.toml", "[dependencies]\nserde = \"1\"\n")],
+        );
+        let res = Removal::plan(
+            &manifest(&dir),
+            "member",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        );
+        assert!(matches!(res, Err(Errors::DependencyNotDeclared(..))));
+    }
+
+    #[test]
+    fn reverts_only_an_unchanged_manifest() {
+        let content = "[dependencies]\nlog = \"0.4\"\n";
+        let dir = TempDir::new("revert", &[("Cargo.toml", content)]);
+        let removal = Removal::plan(
+            &manifest(&dir),
+            "member",
+            "log",
+            &[declared(DependencyKind::Normal, None)],
+        )
+        .unwrap();
+        removal.apply().unwrap();
+        removal.revert().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&removal.manifest_path).unwrap(),
+            content
+        );
+
+        removal.apply().unwrap();
+        std::fs::write(&removal.manifest_path, "[dependencies]\nserde = \"1\"\n").unwrap();
+        assert!(matches!(removal.revert(), Err(Errors::ManifestChanged(_))));
+        assert_eq!(
+            std::fs::read_to_string(&removal.manifest_path).unwrap(),
+            "[dependencies]\nserde = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn diff_keeps_context_around_changes_only() {
+        let res = diff("a\nb\nc\nd\ne\nf\ng\nh\n", "a\nb\nc\nd\nE\nf\ng\nh\n");
+        assert_eq!(
+            res,
+            [
+                DiffLine::Skipped(2),
+                DiffLine::Unchanged("c".to_string()),
+                DiffLine::Unchanged("d".to_string()),
+                DiffLine::Removed("e".to_string()),
+                DiffLine::Added("E".to_string()),
+                DiffLine::Unchanged("f".to_string()),
+                DiffLine::Unchanged("g".to_string()),
+                DiffLine::Skipped(1),
+            ]
+        );
+        assert_eq!(diff("a\nb\n", "a\nb\n"), [DiffLine::Skipped(2)]);
+    }
+}
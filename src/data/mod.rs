@@ -1,4 +1,31 @@
+mod advisory;
+mod artifacts;
+mod cargo;
+mod code;
+mod graph;
+mod index;
+mod license;
+mod loader;
+mod lockfile;
+mod manifest;
+mod size;
 mod state;
+#[cfg(test)]
+mod testing;
+mod timings;
 
-pub use state::DataState;
-pub use state::Metadata;
+pub use advisory::{Advisory, AdvisoryDb, AdvisoryKind};
+pub use artifacts::ArtifactSizes;
+pub use cargo::{
+    CargoMetadata, DepKindInfo, DependencyKind, FeatureToggle, MetadataOptions, Node, NodeDep,
+    Package, Platform, Resolve, is_crates_io,
+};
+pub use code::{CodeStats, UnsafeLint, Unsafety};
+pub use graph::{Graph, IndexSet, Reach, WORKSPACE_ID};
+pub use index::{AllUpdates, Bump, RegistryIndex, Updates};
+pub use license::{LicenseIssue, Policy};
+pub use loader::{LoadEvent, LoadProgress, Loader};
+pub use manifest::{Declaration, DiffLine, Removal};
+pub use size::{Size, SizeMode, SizeStatus};
+pub use state::{DataState, LICENSE_FILTER, ManifestEdit, Metadata, NAME_FILTER};
+pub use timings::{Timing, Timings};
@@ -0,0 +1,160 @@
+use crate::data::Metadata;
+use crate::data::cargo::cargo_home;
+use std::path::{Path, PathBuf};
+
+/// How much a [`Size`] can be trusted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SizeStatus {
+    /// Neither the archive nor the sources were found on this machine.
+    #[default]
+    Unknown,
+    /// There is no archive, the unpacked sources stand in for it.
+    Estimated,
+    /// The `.crate` archive was found in the registry cache.
+    Measured,
+}
+
+impl SizeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizeStatus::Unknown => "unknown",
+            SizeStatus::Estimated => "estimated",
+            SizeStatus::Measured => "measured",
+        }
+    }
+}
+
+/// The size of the sources of a package.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Size {
+    /// The compressed `.crate` archive, only registry packages have one.
+    pub archive: Option<u64>,
+    /// The sources in the package directory, without build output and nested packages.
+    pub unpacked: Option<u64>,
+    pub status: SizeStatus,
+}
+
+impl Size {
+    /// The figure shown in the size columns: the archive size, or the unpacked size without an archive.
+    pub fn bytes(&self) -> Option<u64> {
+        self.archive.or(self.unpacked)
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.status != SizeStatus::Unknown
+    }
+}
+
+/// What the size columns show.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum SizeMode {
+    /// The size of the sources, see [`Size::bytes`].
+    #[default]
+    Source,
+    /// The size of the compiled artifacts in the given profile directory of the target directory.
+    Compiled(String),
+}
+
+impl SizeMode {
+    pub fn bytes(&self, metadata: &Metadata) -> Option<u64> {
+        match self {
+            SizeMode::Source => metadata.size.bytes(),
+            SizeMode::Compiled(profile) => metadata.artifacts.get(profile).copied(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            SizeMode::Source => "source".to_string(),
+            SizeMode::Compiled(profile) => format!("compiled ({})", profile),
+        }
+    }
+
+    /// Source, then every profile in turn.
+    pub fn next(&self, profiles: &[String]) -> SizeMode {
+        let next = match self {
+            SizeMode::Source => profiles.first(),
+            SizeMode::Compiled(profile) => profiles
+                .iter()
+                .position(|other| other == profile)
+                .and_then(|position| profiles.get(position + 1)),
+        };
+        next.map_or(SizeMode::Source, |profile| {
+            SizeMode::Compiled(profile.clone())
+        })
+    }
+}
+
+/// Measures the package whose manifest is at `manifest_path`.
+///
+/// Registry packages, whether crates.io, sparse or alternate registries, are looked up in
+/// `registry/cache` of `CARGO_HOME` next to their extracted sources in `registry/src`.
+/// Git checkouts, path, workspace and vendored packages have no archive, only their
+/// directory is measured.
+pub fn measure(manifest_path: &str) -> Size {
+    let Some(package_dir) = Path::new(manifest_path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    else {
+        return Size::default();
+    };
+    let archive = registry_archive(package_dir)
+        .and_then(|archive| std::fs::metadata(archive).ok())
+        .map(|metadata| metadata.len());
+    let unpacked = package_dir.is_dir().then(|| dir_size(package_dir));
+    Size {
+        archive,
+        unpacked,
+        status: match (archive, unpacked) {
+            (Some(_), _) => SizeStatus::Measured,
+            (None, Some(_)) => SizeStatus::Estimated,
+            (None, None) => SizeStatus::Unknown,
+        },
+    }
+}
+
+/// `registry/cache/<index>/<name>-<version>.crate` for `registry/src/<index>/<name>-<version>`.
+fn registry_archive(package_dir: &Path) -> Option<PathBuf> {
+    let registry_src = cargo_home()?.join("registry").join("src");
+    let relative = package_dir.strip_prefix(&registry_src).ok()?;
+    let mut components = relative.components();
+    let index = components.next()?.as_os_str();
+    let package = components.next()?.as_os_str().to_str()?;
+    if components.next().is_some() {
+        return None;
+    }
+    Some(
+        registry_src
+            .parent()?
+            .join("cache")
+            .join(index)
+            .join(format!("{}.crate", package)),
+    )
+}
+
+/// The size of all files below `dir`, skipping `target`, `.git` and nested packages.
+fn dir_size(dir: &Path) -> u64 {
+    let mut res = 0;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                let path = entry.path();
+                let skipped = matches!(entry.file_name().to_str(), Some("target" | ".git"))
+                    || path.join("Cargo.toml").exists();
+                if !skipped {
+                    stack.push(path);
+                }
+            } else if metadata.is_file() {
+                res += metadata.len();
+            }
+        }
+    }
+    res
+}
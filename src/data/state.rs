@@ -1,97 +1,863 @@
-use crate::ui::OrderBy;
+use crate::data::{
+    Advisory, AdvisoryDb, CodeStats, DependencyKind, FeatureToggle, Graph, IndexSet, LicenseIssue,
+    LoadProgress, MetadataOptions, Platform, Policy, Reach, Removal, Size, SizeMode, Timing,
+    Updates, WORKSPACE_ID, is_crates_io, license,
+};
+use crate::ui::{OrderBy, Share};
+use semver::Version;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Deref;
 // use log::error;
 
+/// Starts a filter matching the normalized license instead of the name.
+pub const LICENSE_FILTER: &str = "license:";
+
+/// Starts a filter matching the whole name instead of a part of it.
+pub const NAME_FILTER: &str = "name:";
+
+/// How many paths the paths popup lists at most.
+const PATH_LIMIT: usize = 100;
+
 #[derive(Debug, Clone, Default)]
 pub struct Metadata {
+    pub id: String,
     pub name: String,
     pub version: String,
     pub license: String,
-    pub size: u64,
+    /// The file the manifest points to instead of or besides naming a license.
+    pub license_file: String,
+    /// Where the package comes from, `None` for path packages.
+    pub source: Option<String>,
+    pub size: Size,
+    /// The size of the compiled artifacts, by profile directory.
+    pub artifacts: BTreeMap<String, u64>,
+    /// The compile time in the last `cargo build --timings` report.
+    pub timing: Option<Timing>,
+    /// The lines of the `.rs` files in the package directory.
+    pub code: Option<CodeStats>,
+    /// The newer releases in the registry index, `None` if the crate is not in the index.
+    pub updates: Option<Updates>,
     pub documentation: String,
     pub description: String,
-    pub dependencies: Vec<String>,
+    pub manifest_path: String,
+    /// The feature table of the manifest.
+    pub features: BTreeMap<String, Vec<String>>,
+    /// The features the resolver activated.
+    pub enabled_features: Vec<String>,
+    /// The features each dependency is declared with, by crate name, `default` unless turned off.
+    pub dependency_features: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// A package as listed below the parent of a table.
+#[derive(Debug, Clone, Default)]
+pub struct Listed {
+    /// The index of the package in the graph.
+    pub index: usize,
+    /// How this package is reached from the parent.
+    pub kinds: Vec<DependencyKind>,
+    /// The `cfg(..)` expressions this package is restricted to below the parent, empty if unconditional.
+    pub platforms: Vec<String>,
+}
+
+/// A listed package together with its metadata, borrowed from the state.
+#[derive(Debug, Clone, Copy)]
+pub struct DepView<'a> {
+    pub index: usize,
+    pub metadata: &'a Metadata,
+    pub kinds: &'a [DependencyKind],
+    pub platforms: &'a [String],
+}
+
+impl Deref for DepView<'_> {
+    type Target = Metadata;
+
+    fn deref(&self) -> &Metadata {
+        self.metadata
+    }
 }
 
+/// A row of the workspace overview.
+#[derive(Debug, Clone, Default)]
+pub struct MemberSummary {
+    pub index: usize,
+    pub name: String,
+    pub version: String,
+    pub direct_count: usize,
+    pub transitive_count: usize,
+    /// The combined size of all transitive dependencies.
+    pub transitive_size: u64,
+}
+
+/// A crate resolved at more than one version, for the duplicates view.
+#[derive(Debug, Clone, Default)]
+pub struct Duplicate {
+    pub name: String,
+    pub versions: Vec<DuplicateVersion>,
+    /// The size of every version but the largest, what settling on one version would save at least.
+    pub wasted: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateVersion {
+    pub version: String,
+    pub size: Option<u64>,
+    /// The packages depending on this version, with their version if their name is duplicated as well.
+    pub parents: Vec<String>,
+}
+
+/// What the root would lose without a package: the package and everything only it leads to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Exclusive {
+    pub size: u64,
+    pub count: usize,
+}
+
+/// The dominator tree below a root, kept until the graph, the root or the shown kinds change.
+#[derive(Debug)]
+struct Dominance {
+    root: usize,
+    kinds: BTreeSet<DependencyKind>,
+    dominators: Vec<Option<usize>>,
+    /// The packages reachable from the root in reverse postorder.
+    order: Vec<usize>,
+}
+
+/// Everything building a package builds: the package and its normal and build dependencies, each counted once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cumulative {
+    pub size: u64,
+    pub count: usize,
+    pub unsafe_count: usize,
+}
+
+/// What the resolved graph holds, compared before and after a manifest edit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Footprint {
+    pub count: usize,
+    pub size: u64,
+}
+
+/// The last manifest edit, kept to report what it changed and to undo it.
+#[derive(Debug, Clone)]
+pub struct ManifestEdit {
+    pub removal: Removal,
+    pub before: Footprint,
+    /// Filled once the graph is resolved again.
+    pub after: Option<Footprint>,
+    /// Whether the manifest was written back as it was.
+    pub undone: bool,
+}
+
+/// The packages of the view sharing a license, for the license summary.
+#[derive(Debug, Clone, Default)]
+pub struct LicenseGroup {
+    /// The normalized license expression.
+    pub license: String,
+    pub count: usize,
+    pub size: u64,
+    /// The crate names, each version of a crate counted but listed once.
+    pub names: Vec<String>,
+}
+
+/// An advisory affecting a resolved package, for the advisories view.
+#[derive(Debug, Clone, Default)]
+pub struct Finding {
+    pub index: usize,
+    /// The index of the advisory in the database.
+    pub advisory: usize,
+    /// The shortest path from the root to the package, empty if the root does not depend on it.
+    pub path: Vec<usize>,
+}
+
+/// A package on a path from the root, for the paths popup.
+#[derive(Debug, Clone, Default)]
+pub struct PathStep {
+    pub index: usize,
+    /// How the previous package depends on this one, empty for the root.
+    pub kinds: Vec<DependencyKind>,
+    /// The features the previous package declares this one with.
+    pub features: Vec<String>,
+}
 
 pub struct DataState {
     pub selected_index: usize,
-    pub deps_map: HashMap<String, Metadata>,
-    pub level1_deps: Vec<Metadata>,
-    pub level2_deps: Vec<Metadata>,
-    pub selected_package: Vec<Metadata>,
+    pub graph: Graph,
+    pub level1_deps: Vec<Listed>,
+    pub level2_deps: Vec<Listed>,
+    /// The navigation path as graph indices, starting with the root.
+    pub selected_package: Vec<usize>,
     pub filter_input: String,
     pub sorting_asc: bool,
     pub is_direct: bool,
-    order: OrderBy
+    /// Whether the tables list dependents instead of dependencies.
+    pub is_reverse: bool,
+    /// The navigation path to return to when leaving the dependents.
+    pub forward_path: Vec<usize>,
+    pub shown_kinds: BTreeSet<DependencyKind>,
+    /// The options the graph was resolved with.
+    pub options: MetadataOptions,
+    /// The choices of the platform picker, filled when it is first opened.
+    pub platforms: Vec<Platform>,
+    /// The package ids of all workspace members.
+    pub workspace_members: Vec<String>,
+    /// The rows of the workspace overview, filled whenever it is opened.
+    pub workspace: Vec<MemberSummary>,
+    /// The rows of the duplicates view, filled whenever it is opened.
+    pub duplicates: Vec<Duplicate>,
+    /// The paths from the root to the selected package, filled whenever the paths popup is opened.
+    pub paths: Vec<Vec<PathStep>>,
+    /// How far the background load has come, `None` when nothing is loading.
+    pub loading: Option<LoadProgress>,
+    pub size_mode: SizeMode,
+    /// The profile directories artifacts were found in.
+    pub profiles: Vec<String>,
+    /// The package and everything it builds on, by graph index, collected once per graph.
+    closures: Vec<IndexSet>,
+    /// The sums over the closure of every package, by graph index.
+    pub cumulative: Vec<Cumulative>,
+    dominance: Option<Dominance>,
+    /// The dominated packages of everything reachable from the root, by graph index, empty for dependents.
+    pub exclusive: Vec<Option<Exclusive>>,
+    /// What the percentage column compares.
+    pub share: Share,
+    /// The licenses the crates may use, if the project has a policy.
+    pub license_policy: Option<Policy>,
+    /// What is wrong with the license of every package, by graph index.
+    license_issues: Vec<Option<LicenseIssue>>,
+    /// The normalized license of every package, by graph index.
+    licenses: Vec<String>,
+    /// The rows of the license summary, filled whenever it is opened.
+    pub license_groups: Vec<LicenseGroup>,
+    /// The local RustSec advisory database, if there is one.
+    pub advisory_db: Option<AdvisoryDb>,
+    /// The advisories affecting every package, by graph index.
+    advisory_matches: Vec<Vec<usize>>,
+    /// The rows of the advisories view, filled whenever it is opened.
+    pub findings: Vec<Finding>,
+    /// The packages with license issues, filled whenever the license issues view is opened.
+    pub flagged: Vec<usize>,
+    /// The dependency removal waiting for confirmation.
+    pub removal: Option<Removal>,
+    pub last_edit: Option<ManifestEdit>,
+    order: OrderBy,
 }
 
 impl DataState {
-    pub fn get_filter_deps(&self) -> Vec<Metadata> {
-        self.level1_deps.iter()
-            .filter(|x| self.filter_input.is_empty() || x.name.contains(self.filter_input.as_str()))
-            .cloned()
+    pub fn get_filter_deps(&self) -> Vec<DepView<'_>> {
+        self.level1_deps
+            .iter()
+            .map(|listed| self.view(listed))
+            .filter(|x| {
+                if let Some(license) = self.filter_input.strip_prefix(LICENSE_FILTER) {
+                    self.license(x.index) == license
+                } else if let Some(name) = self.filter_input.strip_prefix(NAME_FILTER) {
+                    x.name == name
+                } else {
+                    self.filter_input.is_empty() || x.name.contains(self.filter_input.as_str())
+                }
+            })
             .collect()
     }
-    
-    pub fn get_selected_dep(&self) -> Metadata {
-        let filtered_deps = self.get_filter_deps();
-        if filtered_deps.len() > self.selected_index {
-            filtered_deps[self.selected_index].clone()
-        } else { 
-            Metadata::default()
+
+    pub fn get_selected_dep(&self) -> Option<DepView<'_>> {
+        self.get_filter_deps().get(self.selected_index).copied()
+    }
+
+    pub fn view<'a>(&'a self, listed: &'a Listed) -> DepView<'a> {
+        DepView {
+            index: listed.index,
+            metadata: &self.graph[listed.index],
+            kinds: &listed.kinds,
+            platforms: &listed.platforms,
         }
     }
 
+    /// The package the navigation path starts at.
+    pub fn root(&self) -> Option<&Metadata> {
+        self.selected_package
+            .first()
+            .map(|index| &self.graph[*index])
+    }
+
+    /// The package whose dependencies are listed in the left table.
+    pub fn current(&self) -> Option<&Metadata> {
+        self.selected_package
+            .last()
+            .map(|index| &self.graph[*index])
+    }
+
     pub fn default() -> DataState {
         DataState {
             selected_index: 0,
-            deps_map: HashMap::new(),
+            graph: Graph::default(),
             level1_deps: Vec::new(),
             level2_deps: Vec::new(),
             selected_package: Vec::new(),
             filter_input: String::new(),
             is_direct: true,
+            is_reverse: false,
+            forward_path: Vec::new(),
+            shown_kinds: DependencyKind::ALL.into_iter().collect(),
+            options: MetadataOptions::default(),
+            platforms: Vec::new(),
+            workspace_members: Vec::new(),
+            workspace: Vec::new(),
+            duplicates: Vec::new(),
+            paths: Vec::new(),
+            loading: None,
+            size_mode: SizeMode::Source,
+            profiles: Vec::new(),
+            closures: Vec::new(),
+            cumulative: Vec::new(),
+            dominance: None,
+            exclusive: Vec::new(),
+            share: Share::Size,
+            license_policy: None,
+            license_issues: Vec::new(),
+            licenses: Vec::new(),
+            license_groups: Vec::new(),
+            advisory_db: None,
+            advisory_matches: Vec::new(),
+            findings: Vec::new(),
+            flagged: Vec::new(),
+            removal: None,
+            last_edit: None,
             sorting_asc: false,
-            order: OrderBy::Size
+            order: OrderBy::Size,
         }
     }
 
+    pub fn get_level2_dep(&mut self) {
+        self.level2_deps = match self.get_selected_dep() {
+            Some(selected) => self.get_deps(selected.index),
+            None => Vec::new(),
+        };
+    }
+
+    pub fn get_deps(&self, parent: usize) -> Vec<Listed> {
+        let reached = if self.is_reverse {
+            self.graph.reach_dependents(parent, self.is_direct)
+        } else {
+            self.graph.reach(parent, self.is_direct)
+        };
+        let mut res: Vec<Listed> = reached
+            .into_iter()
+            .filter(|(_index, reach)| self.is_shown(reach))
+            .map(|(index, reach)| Listed {
+                index,
+                kinds: reach.kinds.into_iter().collect(),
+                platforms: if reach.platforms.contains(&None) {
+                    Vec::new()
+                } else {
+                    reach
+                        .platforms
+                        .into_iter()
+                        .flatten()
+                        .map(|platform| self.graph.platform(platform).to_string())
+                        .collect()
+                },
+            })
+            .collect();
+        sorting_impl(self, &mut res);
+        res
+    }
 
+    fn is_shown(&self, reach: &Reach) -> bool {
+        reach
+            .kinds
+            .iter()
+            .any(|kind| self.shown_kinds.contains(kind))
+    }
 
-    pub fn get_level2_dep(&mut self) {
-        self.level2_deps = self
-            .get_deps(self.get_selected_dep())
+    /// Summarizes the whole workspace and every member for the workspace overview.
+    pub fn workspace_summary(&self) -> Vec<MemberSummary> {
+        std::iter::once(WORKSPACE_ID)
+            .chain(self.workspace_members.iter().map(String::as_str))
+            .filter_map(|id| self.graph.index_of(id))
+            .map(|index| {
+                let member = &self.graph[index];
+                let transitive: Vec<usize> = self
+                    .graph
+                    .reach(index, false)
+                    .into_iter()
+                    .filter(|(_index, reach)| self.is_shown(reach))
+                    .map(|(index, _reach)| index)
+                    .collect();
+                MemberSummary {
+                    index,
+                    name: member.name.clone(),
+                    version: member.version.clone(),
+                    direct_count: self
+                        .graph
+                        .reach(index, true)
+                        .values()
+                        .filter(|reach| self.is_shown(reach))
+                        .count(),
+                    transitive_count: transitive.len(),
+                    transitive_size: transitive
+                        .iter()
+                        .filter_map(|index| self.size_mode.bytes(&self.graph[*index]))
+                        .sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the crate of the package at `index` is resolved at more than one version.
+    pub fn is_duplicated(&self, index: usize) -> bool {
+        self.graph.versions(&self.graph[index].name).len() > 1
+    }
+
+    /// Every crate resolved at more than one version, the most wasteful first.
+    pub fn find_duplicates(&self) -> Vec<Duplicate> {
+        let mut res: Vec<Duplicate> = self
+            .graph
+            .names()
+            .filter(|(_name, indices)| indices.len() > 1)
+            .map(|(name, indices)| {
+                let mut versions: Vec<DuplicateVersion> = indices
+                    .iter()
+                    .map(|index| DuplicateVersion {
+                        version: self.graph[*index].version.clone(),
+                        size: self.size_mode.bytes(&self.graph[*index]),
+                        parents: self
+                            .graph
+                            .dependents(*index)
+                            .iter()
+                            .map(|edge| &self.graph[edge.index])
+                            .filter(|parent| parent.id != WORKSPACE_ID)
+                            .map(|parent| {
+                                if self.graph.versions(&parent.name).len() > 1 {
+                                    format!("{} {}", parent.name, parent.version)
+                                } else {
+                                    parent.name.clone()
+                                }
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                versions.sort_by(|a, b| a.version.cmp(&b.version));
+                let sizes: Vec<u64> = versions.iter().filter_map(|version| version.size).collect();
+                Duplicate {
+                    name: name.to_string(),
+                    wasted: sizes.iter().sum::<u64>()
+                        - sizes.iter().max().copied().unwrap_or_default(),
+                    versions,
+                }
+            })
+            .collect();
+        res.sort_by(|a, b| b.wasted.cmp(&a.wasted).then_with(|| a.name.cmp(&b.name)));
+        res
+    }
+
+    /// The ways the root depends on the package at `index`, shortest first.
+    pub fn find_paths(&self, index: usize) -> Vec<Vec<PathStep>> {
+        let Some(root) = self.selected_package.first().copied() else {
+            return Vec::new();
+        };
+        self.graph
+            .paths(root, index, PATH_LIMIT)
+            .into_iter()
+            .map(|path| {
+                std::iter::once(PathStep {
+                    index: root,
+                    ..PathStep::default()
+                })
+                .chain(path.windows(2).map(|edge| {
+                    let (parent, child) = (edge[0], edge[1]);
+                    let kinds: BTreeSet<DependencyKind> = self
+                        .graph
+                        .dependencies(parent)
+                        .iter()
+                        .filter(|dependency| dependency.index == child)
+                        .flat_map(|dependency| dependency.kinds.iter().map(|kind| kind.kind))
+                        .collect();
+                    PathStep {
+                        index: child,
+                        kinds: kinds.into_iter().collect(),
+                        features: self.graph[parent]
+                            .dependency_features
+                            .get(&self.graph[child].name)
+                            .map(|features| features.iter().cloned().collect())
+                            .unwrap_or_default(),
+                    }
+                }))
+                .collect()
+            })
+            .collect()
+    }
+
+    /// Shows the package at `position` of `path` as the selected row below its parent.
+    pub fn jump_to(&mut self, path: &[usize], position: usize) {
+        self.is_reverse = false;
+        self.filter_input = String::new();
+        self.level2_deps = Vec::new();
+        match position {
+            0 => self.restore_path(&path[..1], None),
+            _ => self.restore_path(&path[..position], path.get(position).copied()),
+        }
+    }
+
+    /// Finds the dominators below the root unless they are known for the root and the shown kinds.
+    fn dominate(&mut self) {
+        let root = self
+            .selected_package
+            .first()
+            .copied()
+            .filter(|_root| !self.is_reverse);
+        let known = self.dominance.as_ref().is_some_and(|dominance| {
+            Some(dominance.root) == root && dominance.kinds == self.shown_kinds
+        });
+        if known {
+            return;
+        }
+        self.dominance = root.map(|root| {
+            let (dominators, order) = self.graph.dominators(root, &self.shown_kinds);
+            Dominance {
+                root,
+                kinds: self.shown_kinds.clone(),
+                dominators,
+                order,
+            }
+        });
+        self.count_exclusive();
     }
-    pub fn get_deps(&mut self, parent: Metadata) -> Vec<Metadata> {
-        let mut dependency_ids = parent.dependencies.clone();
-        if !self.is_direct {
-            dependency_ids = self.get_deps_ids(parent, HashSet::new()).into_iter().collect();
+
+    /// Sums up the size and number of the packages each package dominates below the root.
+    fn count_exclusive(&mut self) {
+        self.exclusive = Vec::new();
+        let Some(dominance) = &self.dominance else {
+            return;
+        };
+        let mut res: Vec<Option<Exclusive>> = vec![None; self.graph.len()];
+        for index in &dominance.order {
+            res[*index] = Some(Exclusive {
+                size: self
+                    .size_mode
+                    .bytes(&self.graph[*index])
+                    .unwrap_or_default(),
+                count: 1,
+            });
         }
-        let mut res = dependency_ids.iter()
-            .map(|id| self.get_metadata(String::from(id)))
+        // a package comes after its dominator in reverse postorder
+        for index in dominance.order.iter().skip(1).rev() {
+            if let (Some(dominator), Some(own)) = (dominance.dominators[*index], res[*index]) {
+                let total = res[dominator].get_or_insert_default();
+                total.size += own.size;
+                total.count += own.count;
+            }
+        }
+        self.exclusive = res;
+    }
+
+    /// The size and number of packages the root would lose without the package at `index`.
+    pub fn exclusive(&self, index: usize) -> Option<Exclusive> {
+        self.exclusive.get(index).copied().flatten()
+    }
+
+    /// Switches the percentage column between the own and the exclusive size.
+    pub fn next_share(&mut self) {
+        self.share = match self.share {
+            Share::Size => Share::Exclusive,
+            Share::Exclusive => Share::Size,
+        };
+    }
+
+    /// Replaces the graph, dropping everything derived from the previous one.
+    pub fn set_graph(&mut self, graph: Graph) {
+        self.graph = graph;
+        self.dominance = None;
+        self.collect_closures();
+    }
+
+    /// Collects the normal and build dependencies of every package, once for each new graph.
+    fn collect_closures(&mut self) {
+        self.closures = self.graph.closures();
+        self.count_cumulative();
+    }
+
+    /// Sums up the size, number and `unsafe` uses over the closure of every package,
+    /// and the sizes every package dominates below the root.
+    pub fn count_cumulative(&mut self) {
+        self.count_exclusive();
+        self.cumulative = self
+            .closures
+            .iter()
+            .map(|closure| {
+                let mut res = Cumulative {
+                    count: closure.len(),
+                    ..Cumulative::default()
+                };
+                for metadata in closure.iter().map(|index| &self.graph[index]) {
+                    res.size += self.size_mode.bytes(metadata).unwrap_or_default();
+                    res.unsafe_count += metadata.code.map_or(0, |code| code.unsafety.count());
+                }
+                res
+            })
+            .collect();
+    }
+
+    /// The sums over the package at `index` and everything it builds on.
+    pub fn cumulative(&self, index: usize) -> Cumulative {
+        self.cumulative.get(index).copied().unwrap_or_default()
+    }
+
+    /// Checks the license of every package against the policy, once for each new graph and its members.
+    pub fn check_licenses(&mut self) {
+        self.license_issues = self
+            .graph
+            .packages()
+            .map(|(_index, metadata)| {
+                let member =
+                    metadata.id == WORKSPACE_ID || self.workspace_members.contains(&metadata.id);
+                license::check(
+                    self.license_policy.as_ref(),
+                    &metadata.name,
+                    &metadata.license,
+                    &metadata.license_file,
+                    member,
+                )
+            })
             .collect();
-        sorting_impl(&mut res, self.order, self.sorting_asc);
+        self.licenses = self
+            .graph
+            .packages()
+            .map(|(_index, metadata)| license::normalize(&metadata.license, &metadata.license_file))
+            .collect();
+    }
+
+    /// The normalized license of the package at `index`.
+    pub fn license(&self, index: usize) -> &str {
+        self.licenses.get(index).map_or("", String::as_str)
+    }
+
+    /// Groups the listed packages by license, the most common first.
+    pub fn license_summary(&self) -> Vec<LicenseGroup> {
+        let mut groups: BTreeMap<&str, LicenseGroup> = BTreeMap::new();
+        for listed in &self.level1_deps {
+            let metadata = &self.graph[listed.index];
+            let group = groups.entry(self.license(listed.index)).or_default();
+            group.count += 1;
+            group.size += self.size_mode.bytes(metadata).unwrap_or_default();
+            group.names.push(metadata.name.clone());
+        }
+        let mut res: Vec<LicenseGroup> = groups
+            .into_iter()
+            .map(|(license, mut group)| {
+                group.license = license.to_string();
+                group.names.sort();
+                group.names.dedup();
+                group
+            })
+            .collect();
+        res.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| b.size.cmp(&a.size)));
         res
     }
-    
-    fn get_deps_ids(&mut self, parent: Metadata, mut visited: HashSet<String>) -> HashSet<String> {
-        for id in parent.dependencies.iter() {
-            if !visited.contains(id) {
-                visited.insert(String::from(id));
-                let target = self.get_metadata(String::from(id));
-                visited = self.get_deps_ids(target, visited.clone());
+
+    /// What is wrong with the license of the package at `index`, if anything.
+    pub fn license_issue(&self, index: usize) -> Option<&LicenseIssue> {
+        self.license_issues.get(index).and_then(Option::as_ref)
+    }
+
+    /// The packages with license issues, violations first.
+    pub fn find_license_issues(&self) -> Vec<usize> {
+        let mut res: Vec<usize> = self
+            .graph
+            .packages()
+            .filter(|(index, metadata)| {
+                metadata.id != WORKSPACE_ID && self.license_issue(*index).is_some()
+            })
+            .map(|(index, _metadata)| index)
+            .collect();
+        res.sort_by_key(|index| {
+            let metadata = &self.graph[*index];
+            (
+                !matches!(self.license_issue(*index), Some(LicenseIssue::Violation(_))),
+                metadata.name.clone(),
+                metadata.version.clone(),
+            )
+        });
+        res
+    }
+
+    /// Matches every crates.io package against the advisory database, once for each new graph.
+    pub fn check_advisories(&mut self) {
+        let Some(db) = &self.advisory_db else {
+            return;
+        };
+        self.advisory_matches = self
+            .graph
+            .packages()
+            .map(|(_index, metadata)| match &metadata.source {
+                Some(source) if is_crates_io(source) => {
+                    db.matching(&metadata.name, &metadata.version)
+                }
+                _ => Vec::new(),
+            })
+            .collect();
+    }
+
+    /// The advisories affecting the package at `index`, the most urgent first.
+    pub fn advisories(&self, index: usize) -> Vec<&Advisory> {
+        let Some(db) = &self.advisory_db else {
+            return Vec::new();
+        };
+        let mut res: Vec<&Advisory> = self
+            .advisory_matches
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|advisory| &db.advisories[*advisory])
+            .collect();
+        res.sort_by_key(|advisory| std::cmp::Reverse(advisory.rank()));
+        res
+    }
+
+    /// Every advisory affecting a package, the most urgent first, with the way the root depends on the package.
+    pub fn find_advisories(&self) -> Vec<Finding> {
+        let Some(db) = &self.advisory_db else {
+            return Vec::new();
+        };
+        let root = self.selected_package.first().copied();
+        let mut res: Vec<Finding> = self
+            .advisory_matches
+            .iter()
+            .enumerate()
+            .flat_map(|(index, advisories)| {
+                let path = match root {
+                    Some(root) if root == index => vec![root],
+                    Some(root) => self
+                        .graph
+                        .paths(root, index, 1)
+                        .into_iter()
+                        .next()
+                        .unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                advisories.iter().map(move |advisory| Finding {
+                    index,
+                    advisory: *advisory,
+                    path: path.clone(),
+                })
+            })
+            .collect();
+        res.sort_by(|a, b| {
+            db.advisories[b.advisory]
+                .rank()
+                .cmp(&db.advisories[a.advisory].rank())
+                .then_with(|| self.graph[a.index].name.cmp(&self.graph[b.index].name))
+        });
+        res
+    }
+
+    /// The compatible release to move to if the resolved version of the package at `index` is yanked.
+    ///
+    /// `Some(None)` if it is yanked and no release could replace it.
+    pub fn yanked(&self, index: usize) -> Option<Option<&Version>> {
+        self.graph[index]
+            .updates
+            .as_ref()
+            .filter(|updates| updates.yanked)
+            .map(|updates| updates.replacement.as_ref())
+    }
+
+    /// The number and size of every resolved package.
+    pub fn footprint(&self) -> Footprint {
+        let mut res = Footprint::default();
+        for (_index, metadata) in self
+            .graph
+            .packages()
+            .filter(|(_index, metadata)| metadata.id != WORKSPACE_ID)
+        {
+            res.count += 1;
+            res.size += self.size_mode.bytes(metadata).unwrap_or_default();
+        }
+        res
+    }
+
+    /// Makes the package at `index` the root of the navigation path.
+    pub fn select_root(&mut self, index: usize) {
+        self.is_reverse = false;
+        self.filter_input = String::new();
+        self.level2_deps = Vec::new();
+        self.restore_path(&[index], None);
+    }
+
+    /// Lists the dependents of the selected package, or returns to the dependencies it was selected in.
+    pub fn toggle_reverse(&mut self) {
+        self.filter_input = String::new();
+        self.level2_deps = Vec::new();
+        if self.is_reverse {
+            self.is_reverse = false;
+            let selected = self.selected_package.first().copied();
+            let path = std::mem::take(&mut self.forward_path);
+            self.restore_path(&path, selected);
+        } else if let Some(selected) = self
+            .get_selected_dep()
+            .map(|dep| dep.index)
+            .or(self.selected_package.last().copied())
+        {
+            self.is_reverse = true;
+            self.forward_path = self.selected_package.clone();
+            self.restore_path(&[selected], None);
+        }
+    }
+
+    /// The entries of the feature panel, for the root package.
+    pub fn feature_toggles(&self) -> Vec<FeatureToggle> {
+        let mut res = vec![FeatureToggle::All, FeatureToggle::Default];
+        if let Some(root) = self.root() {
+            res.extend(
+                root.features
+                    .keys()
+                    .filter(|name| *name != "default")
+                    .map(|name| FeatureToggle::Feature(name.clone())),
+            );
+        }
+        res
+    }
+
+    pub fn toggle_kind(&mut self, kind: DependencyKind) {
+        if !self.shown_kinds.remove(&kind) {
+            self.shown_kinds.insert(kind);
+        }
+        self.switch_mode();
+    }
+
+    /// Walks `path` (graph indices from the root) as far as it is still listed,
+    /// then selects `selected` in the last level if it is listed there.
+    pub fn restore_path(&mut self, path: &[usize], selected: Option<usize>) {
+        let Some(root) = path
+            .first()
+            .copied()
+            .filter(|root| *root < self.graph.len())
+        else {
+            return;
+        };
+        self.selected_package = vec![root];
+        self.dominate();
+        self.level1_deps = self.get_deps(root);
+        for index in path.iter().skip(1) {
+            if !self.level1_deps.iter().any(|dep| dep.index == *index) {
+                break;
             }
+            self.selected_package.push(*index);
+            self.level1_deps = self.get_deps(*index);
         }
-        visited
+        self.selected_index = self
+            .get_filter_deps()
+            .iter()
+            .position(|dep| Some(dep.index) == selected)
+            .unwrap_or_default();
+        self.get_level2_dep();
     }
 
-    pub fn get_metadata(&self, id: String) -> Metadata {
-        self.deps_map
-            .get(id.as_str())
-            .cloned()
-            .unwrap_or(Metadata::default())
+    /// Rebuilds the shown lists from the graph, keeping the path and the selected row.
+    pub fn refresh(&mut self) {
+        let path = self.selected_package.clone();
+        let selected = self.get_selected_dep().map(|dep| dep.index);
+        self.restore_path(&path, selected);
     }
 
     pub fn order_by(&mut self, order: OrderBy) {
@@ -101,15 +867,29 @@ impl DataState {
 
     pub fn sorting(&mut self, sorting_asc: bool) {
         self.sorting_asc = sorting_asc;
-        sorting_impl(&mut self.level1_deps, self.order, sorting_asc);
-        sorting_impl(&mut self.level2_deps, self.order, sorting_asc);
-        self.selected_index = self.get_filter_deps().len() - 1 - self.selected_index;
+        let mut level1_deps = std::mem::take(&mut self.level1_deps);
+        let mut level2_deps = std::mem::take(&mut self.level2_deps);
+        sorting_impl(self, &mut level1_deps);
+        sorting_impl(self, &mut level2_deps);
+        self.level1_deps = level1_deps;
+        self.level2_deps = level2_deps;
+        self.selected_index = self
+            .get_filter_deps()
+            .len()
+            .saturating_sub(1 + self.selected_index);
+    }
+
+    /// Switches the size columns to the next profile, re-sorting the lists.
+    pub fn next_size_mode(&mut self) {
+        self.size_mode = self.size_mode.next(&self.profiles);
+        self.count_cumulative();
+        self.refresh();
     }
 
     pub fn switch_mode(&mut self) {
         if let Some(last_dep) = self.selected_package.last() {
             self.selected_index = 0;
-            self.level1_deps = self.get_deps(last_dep.clone());
+            self.level1_deps = self.get_deps(*last_dep);
             self.get_level2_dep();
         } else {
             self.level1_deps = Vec::new();
@@ -117,17 +897,46 @@ impl DataState {
     }
 }
 
-fn sorting_impl(vec: &mut Vec<Metadata>, order: OrderBy, sorting_asc: bool) {
+fn sorting_impl(state: &DataState, vec: &mut [Listed]) {
+    let (graph, size_mode) = (&state.graph, &state.size_mode);
     vec.sort_by(|left, right| {
-        let compare = |a: &Metadata, b: &Metadata| -> Ordering {
-            match order {
+        let compare = |a: &Listed, b: &Listed| -> Ordering {
+            let (exclusive_a, exclusive_b) = (state.exclusive(a.index), state.exclusive(b.index));
+            let (cumulative_a, cumulative_b) =
+                (state.cumulative(a.index), state.cumulative(b.index));
+            let (a, b) = (&graph[a.index], &graph[b.index]);
+            match state.order {
                 OrderBy::Name => a.name.cmp(&b.name),
                 OrderBy::Version => a.version.cmp(&b.version),
-                OrderBy::Size => a.size.cmp(&b.size),
+                OrderBy::Size => size_mode.bytes(a).cmp(&size_mode.bytes(b)),
+                OrderBy::Files => a
+                    .code
+                    .map(|code| code.total().files)
+                    .cmp(&b.code.map(|code| code.total().files)),
+                OrderBy::Lines => a
+                    .code
+                    .map(|code| code.total().code)
+                    .cmp(&b.code.map(|code| code.total().code)),
+                OrderBy::Unsafe => a
+                    .code
+                    .map(|code| code.unsafety.count())
+                    .cmp(&b.code.map(|code| code.unsafety.count())),
+                OrderBy::BuildTime => a
+                    .timing
+                    .map(|timing| timing.total)
+                    .cmp(&b.timing.map(|timing| timing.total)),
+                OrderBy::ExclusiveSize => exclusive_a
+                    .map(|exclusive| exclusive.size)
+                    .cmp(&exclusive_b.map(|exclusive| exclusive.size)),
+                OrderBy::ExclusiveCount => exclusive_a
+                    .map(|exclusive| exclusive.count)
+                    .cmp(&exclusive_b.map(|exclusive| exclusive.count)),
+                OrderBy::CumulativeSize => cumulative_a.size.cmp(&cumulative_b.size),
+                OrderBy::CumulativeCount => cumulative_a.count.cmp(&cumulative_b.count),
             }
         };
 
-        if sorting_asc {
+        if state.sorting_asc {
             compare(left, right)
         } else {
             compare(right, left)
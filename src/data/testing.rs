@@ -0,0 +1,31 @@
+use std::path::{Path, PathBuf};
+
+/// A directory of its own in the temporary directory, removed again when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates the directory of `test` with `files` written into it.
+    pub fn new(test: &str, files: &[(&str, &str)]) -> TempDir {
+        let path =
+            std::env::temp_dir().join(format!("crates-inspector-{}-{}", std::process::id(), test));
+        std::fs::create_dir_all(&path).unwrap();
+        for (file, content) in files {
+            let file = path.join(file);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
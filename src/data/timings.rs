@@ -0,0 +1,160 @@
+use crate::data::Package;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The compile time of each package id, summed over all of its units.
+pub type Timings = HashMap<String, Timing>;
+
+/// How long a package took to compile in the last `cargo build --timings`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Timing {
+    /// The duration of every unit of the package, build scripts included.
+    pub total: Duration,
+    /// Parsing, type checking and writing the metadata, only reported by some cargo versions.
+    pub frontend: Option<Duration>,
+    /// Code generation and linking, only reported by some cargo versions.
+    pub codegen: Option<Duration>,
+}
+
+impl AddAssign for Timing {
+    fn add_assign(&mut self, other: Timing) {
+        self.total += other.total;
+        self.frontend = add_known(self.frontend, other.frontend);
+        self.codegen = add_known(self.codegen, other.codegen);
+    }
+}
+
+fn add_known(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// A unit in the `UNIT_DATA` of the HTML report.
+#[derive(Debug, Deserialize)]
+struct ReportUnit {
+    name: String,
+    version: String,
+    duration: f64,
+    /// When the metadata was ready, written by cargo before it reported sections.
+    rmeta_time: Option<f64>,
+    /// `[name, {start, end}]` pairs like `frontend` and `codegen`.
+    sections: Option<Vec<(String, Section)>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Section {
+    start: f64,
+    end: f64,
+}
+
+/// A `timing-info` message of `cargo build --timings=json`.
+#[derive(Debug, Deserialize)]
+struct TimingInfo {
+    reason: String,
+    package_id: String,
+    duration: f64,
+    rmeta_time: Option<f64>,
+}
+
+/// Reads the newest report below `target/cargo-timings`, the HTML report cargo writes or the
+/// output of `--timings=json` saved there with a `.json` extension.
+pub fn scan(target_directory: &str, packages: &[Package]) -> Timings {
+    let Some(report) = newest_report(&Path::new(target_directory).join("cargo-timings")) else {
+        return Timings::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&report) else {
+        return Timings::new();
+    };
+    if report
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        from_json(&content)
+    } else {
+        from_html(&content, packages)
+    }
+}
+
+fn newest_report(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|extension| extension == "html" || extension == "json")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_modified, path)| path)
+}
+
+/// The HTML report only names the package of a unit, the version tells apart several of them.
+fn from_html(content: &str, packages: &[Package]) -> Timings {
+    let mut res = Timings::new();
+    let Some((_before, data)) = content.split_once("const UNIT_DATA = ") else {
+        return res;
+    };
+    let Some(Ok(units)) = serde_json::Deserializer::from_str(data)
+        .into_iter::<Vec<ReportUnit>>()
+        .next()
+    else {
+        return res;
+    };
+    for unit in units {
+        let Some(package) = packages
+            .iter()
+            .find(|package| package.name == unit.name && package.version == unit.version)
+        else {
+            continue;
+        };
+        let section = |name: &str| {
+            unit.sections
+                .iter()
+                .flatten()
+                .find(|(section, _range)| section == name)
+                .map(|(_section, range)| seconds(range.end - range.start))
+        };
+        let timing = match (section("frontend"), section("codegen")) {
+            (None, None) => from_rmeta_time(unit.duration, unit.rmeta_time),
+            (frontend, codegen) => Timing {
+                total: seconds(unit.duration),
+                frontend,
+                codegen,
+            },
+        };
+        *res.entry(package.id.clone()).or_default() += timing;
+    }
+    res
+}
+
+fn from_json(content: &str) -> Timings {
+    let mut res = Timings::new();
+    for info in content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<TimingInfo>(line).ok())
+        .filter(|info| info.reason == "timing-info")
+    {
+        *res.entry(info.package_id).or_default() += from_rmeta_time(info.duration, info.rmeta_time);
+    }
+    res
+}
+
+/// Anything after the metadata was written counts as code generation.
+fn from_rmeta_time(duration: f64, rmeta_time: Option<f64>) -> Timing {
+    Timing {
+        total: seconds(duration),
+        frontend: rmeta_time.map(seconds),
+        codegen: rmeta_time.map(|rmeta_time| seconds(duration - rmeta_time)),
+    }
+}
+
+fn seconds(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
+}
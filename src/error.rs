@@ -10,10 +10,40 @@ pub enum Errors {
     SmallArea,
     #[error("Failed to run cargo metadata.")]
     RunCargoMetadata,
-    #[error("Failed to parse metadata.")]
-    ParseMetadata,
+    #[error("cargo metadata failed: {0}")]
+    CargoMetadataFailed(String),
+    #[error("Failed to parse metadata: {0}")]
+    ParseMetadata(#[from] serde_json::Error),
+    #[error("cargo metadata printed format version {0}, only version {1} is understood.")]
+    MetadataVersion(u32, u32),
     #[error("Cargo.toml not found.")]
     CargoTomlNotFound,
+    #[error("The graph was read from {0} and cannot be resolved again.")]
+    FixedGraph(String),
+    #[error("Cargo.lock not found.")]
+    CargoLockNotFound,
+    #[error("Failed to parse Cargo.lock: {0}")]
+    ParseLockfile(#[from] toml::de::Error),
+    #[error("License policy {0} not found.")]
+    LicensePolicyNotFound(String),
+    #[error("Failed to parse license policy {0}: {1}")]
+    ParseLicensePolicy(String, toml::de::Error),
+    #[error("Advisory database {0} not found, expected a checkout of the RustSec advisory-db.")]
+    AdvisoryDbNotFound(String),
+    #[error("Registry index {0} not found.")]
+    RegistryIndexNotFound(String),
+    #[error("Failed to parse {0}: {1}")]
+    ParseManifest(String, toml_edit::TomlError),
+    #[error("{0} is not a direct dependency of a workspace member.")]
+    NotRemovable(String),
+    #[error("{0} is not declared in {1}.")]
+    DependencyNotDeclared(String, String),
+    #[error("{0} changed since the last edit, not undoing it.")]
+    ManifestChanged(String),
+    #[error("There is no edit to undo.")]
+    NothingToUndo,
+    #[error("{0} Showing Cargo.lock instead.")]
+    LockfileFallback(Box<Errors>),
 }
 
 impl Errors {
@@ -1,19 +1,25 @@
-mod data;
 mod app;
-mod ui;
+mod data;
 mod error;
+mod ui;
 
 use clap::Parser;
 use log::error;
-use ratatui::crossterm::{event::{self, Event, KeyCode}, terminal, ExecutableCommand};
-use ratatui::prelude::*;
-use ratatui::{
-    prelude::CrosstermBackend,
-    Terminal,
+use ratatui::crossterm::{
+    ExecutableCommand,
+    event::{self, Event, KeyCode},
+    terminal,
 };
+use ratatui::prelude::*;
+use ratatui::{Terminal, prelude::CrosstermBackend};
 use std::panic;
+use std::time::Duration;
 
 use crate::app::App;
+use crate::data::{MetadataOptions, Platform};
+
+/// How long the main loop waits for input before picking up background results.
+const TICK_RATE: Duration = Duration::from_millis(100);
 
 // use std::fs::OpenOptions;
 // use std::io::Write;
@@ -26,10 +32,46 @@ struct Args {
 
     #[arg(short, long)]
     license: bool,
+
+    /// Resolve dependencies for this target triple, `host` or `all`
+    #[arg(short, long, default_value = "host")]
+    target: String,
+
+    /// Comma separated list of features to activate
+    #[arg(short = 'F', long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Activate all available features
+    #[arg(long)]
+    all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[arg(long)]
+    no_default_features: bool,
+
+    /// Read the output of `cargo metadata --format-version 1` from this file, `-` for stdin
+    #[arg(short, long, value_name = "PATH")]
+    metadata_file: Option<String>,
+
+    /// Build the graph from Cargo.lock without invoking cargo
+    #[arg(long)]
+    lockfile: bool,
+
+    /// Check licenses against this policy instead of `license-policy.toml` in the project
+    #[arg(long, value_name = "PATH")]
+    license_policy: Option<String>,
+
+    /// Read advisories from this checkout of the RustSec advisory-db instead of `CARGO_HOME/advisory-db`
+    #[arg(long, value_name = "PATH")]
+    advisory_db: Option<String>,
+
+    /// Look up newer releases in this crates.io index instead of the one cargo caches in `CARGO_HOME`
+    #[arg(long, value_name = "PATH")]
+    index_path: Option<String>,
 }
 
 fn main() -> error::Result<()> {
-   /* // Initialize logger
+    /* // Initialize logger
     let log_file = OpenOptions::new()
         .create(true)
         .write(true)
@@ -51,7 +93,7 @@ fn main() -> error::Result<()> {
         .target(env_logger::Target::Pipe(Box::new(log_file)))
         .filter(None, log::LevelFilter::Error)
         .init();*/
-    
+
     let args = Args::parse();
     // === Help Notices etc. ===
     if args.license {
@@ -62,9 +104,24 @@ fn main() -> error::Result<()> {
     // Initialize hooks & terminal (ratatui boilerplate)
     init_hooks()?;
     let mut terminal = init_terminal()?;
-    let (mut app, errors) = App::new(&args.path, |message| draw_loading_screen(&mut terminal, message));
+    let options = MetadataOptions {
+        platform: Platform::parse(&args.target),
+        features: args.features.into_iter().collect(),
+        all_features: args.all_features,
+        no_default_features: args.no_default_features,
+        manifest_path: None,
+        metadata_file: args.metadata_file,
+        lockfile: args.lockfile,
+    };
+    let (mut app, errors) = App::new(
+        &args.path,
+        options,
+        args.license_policy.as_deref(),
+        args.advisory_db.as_deref(),
+        args.index_path.as_deref(),
+    );
     let mut current_error: Option<error::Errors> = errors.into_iter().next_back();
-    
+
     loop {
         terminal.draw(|frame: &mut Frame| {
             let area = frame.area();
@@ -99,14 +156,24 @@ fn main() -> error::Result<()> {
             app.draw(app_area, buf);
         })?;
 
-        if let Event::Key(key) = event::read()? {
+        if event::poll(TICK_RATE)?
+            && let Event::Key(key) = event::read()?
+        {
             match key.code {
-                KeyCode::Char('q'|'Q') => break,
-                _ => app.update(key)
+                KeyCode::Char('q' | 'Q') => break,
+                _ => {
+                    if let Err(e) = app.update(key) {
+                        current_error = Some(e);
+                    }
+                }
             }
         }
+
+        if let Err(e) = app.tick() {
+            current_error = Some(e);
+        }
     }
-    
+
     if let Some(error) = &current_error {
         error!("Error initializing app: {}", error);
     }
@@ -128,7 +195,6 @@ You should have received a copy of the GNU General Public License along with thi
     ");
 }
 
-
 /// Ratatui boilerplate to set up panic hooks
 fn init_hooks() -> error::Result<()> {
     // Get a default panic hook
@@ -142,7 +208,6 @@ fn init_hooks() -> error::Result<()> {
     Ok(())
 }
 
-
 /// Ratatui boilerplate to put the terminal into a TUI state
 fn init_terminal() -> std::io::Result<Terminal<impl ratatui::backend::Backend>> {
     std::io::stdout().execute(terminal::EnterAlternateScreen)?;
@@ -158,25 +223,3 @@ fn restore_terminal() -> std::io::Result<()> {
     terminal::disable_raw_mode()?;
     Ok(())
 }
-
-
-/// Draws nothing but a loading screen with an indexing message.
-/// Temporary screen while the programm is indexing.
-fn draw_loading_screen(
-    terminal: &mut Terminal<impl ratatui::backend::Backend>,
-    message: &str,
-) -> error::Result<()> {
-    // Draw 'loading' screen
-    terminal.draw(|frame| {
-        frame.render_widget(
-            ratatui::widgets::Paragraph::new(message).alignment(Alignment::Center),
-            Layout::vertical([
-                Constraint::Fill(1),
-                Constraint::Length(5),
-                Constraint::Fill(1),
-            ])
-                .split(frame.area())[1],
-        );
-    })?;
-    Ok(())
-}
\ No newline at end of file
@@ -1,7 +1,8 @@
-mod uistyles;
 mod screen;
+mod uistyles;
 
-pub use uistyles::UiStyles;
-pub use screen::Screen;
 pub use screen::DisplayMode;
 pub use screen::OrderBy;
+pub use screen::Screen;
+pub use screen::Share;
+pub use uistyles::UiStyles;
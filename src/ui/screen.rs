@@ -1,10 +1,16 @@
-use crate::data::DataState;
+use crate::data::{
+    Advisory, AdvisoryKind, Bump, CodeStats, DataState, DependencyKind, DiffLine, FeatureToggle,
+    LICENSE_FILTER, LicenseIssue, LoadProgress, Metadata, NAME_FILTER, Size, SizeMode, SizeStatus,
+    Timing, UnsafeLint, Unsafety, Updates,
+};
 use crate::ui::UiStyles;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::prelude::{Color, Line, Span, Style, Text, Widget};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
 use ratatui::{prelude::*, widgets::*};
+use semver::Version;
+use std::time::Duration;
 use tui_textarea::TextArea;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -15,6 +21,24 @@ pub enum DisplayMode {
     Filter,
     Help,
     Sort,
+    /// Picking the target platform to resolve for.
+    Platform,
+    /// Toggling the features of the root package.
+    Features,
+    /// Picking the workspace member to use as root.
+    Workspace,
+    /// Listing the crates resolved at more than one version.
+    Duplicates,
+    /// Listing the paths from the root to the selected package.
+    Paths,
+    /// Listing the crates breaking the license policy or needing a review.
+    LicenseIssues,
+    /// Grouping the listed crates by license.
+    Licenses,
+    /// Listing the advisories affecting resolved packages.
+    Advisories,
+    /// Confirming the removal of a dependency from a manifest.
+    RemoveDependency,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -23,6 +47,32 @@ pub enum OrderBy {
     Size,
     Name,
     Version,
+    /// The number of `.rs` files.
+    Files,
+    /// The lines of code, without comments and blank lines.
+    Lines,
+    /// The uses of `unsafe` in the library code.
+    Unsafe,
+    /// The compile time of the last `cargo build --timings`.
+    BuildTime,
+    /// The size of everything only reachable through a package.
+    ExclusiveSize,
+    /// The number of packages only reachable through a package.
+    ExclusiveCount,
+    /// The size of a package and everything it builds on.
+    CumulativeSize,
+    /// The number of packages a package builds, itself included.
+    CumulativeCount,
+}
+
+/// What the percentage column of the left table compares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Share {
+    /// The own size of each row against the sum of the listed rows.
+    #[default]
+    Size,
+    /// The exclusive size of each row against everything the root depends on.
+    Exclusive,
 }
 
 pub struct Screen {
@@ -30,6 +80,18 @@ pub struct Screen {
     styles: UiStyles,
     pub mode: DisplayMode,
     pub viewport_start: usize,
+    pub platform_index: usize,
+    pub feature_index: usize,
+    pub workspace_index: usize,
+    pub duplicates_index: usize,
+    pub license_issues_index: usize,
+    pub licenses_index: usize,
+    pub advisories_index: usize,
+    pub paths_index: usize,
+    /// The package of the selected path to jump to.
+    pub path_step: usize,
+    /// The first diff line shown in the removal popup.
+    pub removal_scroll: u16,
 }
 
 impl Screen {
@@ -37,6 +99,16 @@ impl Screen {
         let mut res = Screen {
             mode: DisplayMode::View,
             viewport_start: 0,
+            platform_index: 0,
+            feature_index: 0,
+            workspace_index: 0,
+            duplicates_index: 0,
+            license_issues_index: 0,
+            licenses_index: 0,
+            advisories_index: 0,
+            paths_index: 0,
+            path_step: 0,
+            removal_scroll: 0,
             filter_area: TextArea::default(),
             styles: UiStyles::default(),
         };
@@ -46,9 +118,22 @@ impl Screen {
 
     pub fn clear_filter(&mut self, state: &mut DataState) {
         state.selected_index = 0;
+        self.reset_filter_area();
+        state.filter_input = String::from("");
+    }
+
+    /// Empties the filter text box, leaving the state alone.
+    pub fn reset_filter_area(&mut self) {
         self.filter_area = TextArea::default();
         self.style_text_area();
-        state.filter_input = String::from("");
+    }
+
+    /// Fills the filter text box with `text` and applies it.
+    pub fn set_filter(&mut self, state: &mut DataState, text: &str) {
+        self.filter_area = TextArea::from([text]);
+        self.filter_area.move_cursor(tui_textarea::CursorMove::End);
+        self.style_text_area();
+        self.filter(state);
     }
 
     pub fn display(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
@@ -60,6 +145,33 @@ impl Screen {
             DisplayMode::Sort => {
                 self.render_sort(area, buf);
             }
+            DisplayMode::Platform => {
+                self.render_platform(area, buf, state);
+            }
+            DisplayMode::Features => {
+                self.render_features(area, buf, state);
+            }
+            DisplayMode::Workspace => {
+                self.render_workspace(area, buf, state);
+            }
+            DisplayMode::Duplicates => {
+                self.render_duplicates(area, buf, state);
+            }
+            DisplayMode::LicenseIssues => {
+                self.render_license_issues(area, buf, state);
+            }
+            DisplayMode::Licenses => {
+                self.render_licenses(area, buf, state);
+            }
+            DisplayMode::Advisories => {
+                self.render_advisories(area, buf, state);
+            }
+            DisplayMode::Paths => {
+                self.render_paths(area, buf, state);
+            }
+            DisplayMode::RemoveDependency => {
+                self.render_removal(area, buf, state);
+            }
             _ => {}
         }
     }
@@ -69,6 +181,14 @@ impl Screen {
             ("S", "Sort by size"),
             ("N", "Sort by name"),
             ("V", "Sort by version"),
+            ("F", "Sort by file count"),
+            ("L", "Sort by lines of code"),
+            ("U", "Sort by unsafe usage"),
+            ("B", "Sort by build time"),
+            ("E", "Sort by exclusive size"),
+            ("X", "Sort by exclusive count"),
+            ("C", "Sort by cumulative size"),
+            ("T", "Sort by cumulative count"),
             ("R", "Reverse sorting"),
         ];
 
@@ -114,11 +234,756 @@ impl Screen {
         Widget::render(popup_table, br_area, buf);
     }
 
+    fn render_platform(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let height = (state.platforms.len() as u16 + 2).min(area.height.saturating_sub(4));
+        let center_area = popup_area(area, Constraint::Length(50), Constraint::Length(height));
+
+        let visible_rows = center_area.height.saturating_sub(2) as usize;
+        let rows = state
+            .platforms
+            .iter()
+            .enumerate()
+            .skip((self.platform_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(index, platform)| {
+                let style = if index == self.platform_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                Row::new(vec![
+                    Cell::from(if *platform == state.options.platform {
+                        "✓"
+                    } else {
+                        ""
+                    }),
+                    Cell::from(platform.label()),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(rows, [Constraint::Length(1), Constraint::Fill(1)])
+            .column_spacing(1)
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        "Target platform",
+                        self.styles.help_style,
+                    ))
+                    .title_bottom(
+                        Line::from(vec![
+                            Span::styled("↵", self.styles.hotkey_style),
+                            Span::styled(": Resolve──", self.styles.text_style),
+                            Span::styled("Esc", self.styles.hotkey_style),
+                            Span::styled(": Close", self.styles.text_style),
+                        ])
+                        .right_aligned(),
+                    ),
+            );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_features(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let toggles = state.feature_toggles();
+        let root = state.root().cloned().unwrap_or_default();
+
+        let height = (toggles.len() as u16 + 3).min(area.height.saturating_sub(4));
+        let center_area = popup_area(area, Constraint::Length(80), Constraint::Length(height));
+
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = toggles
+            .iter()
+            .enumerate()
+            .skip((self.feature_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(index, toggle)| {
+                let style = if index == self.feature_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                let (active, enables) = match toggle {
+                    FeatureToggle::All => (false, Vec::new()),
+                    FeatureToggle::Default => (
+                        root.enabled_features
+                            .iter()
+                            .any(|feature| feature == "default"),
+                        root.features.get("default").cloned().unwrap_or_default(),
+                    ),
+                    FeatureToggle::Feature(name) => (
+                        root.enabled_features.contains(name),
+                        root.features.get(name).cloned().unwrap_or_default(),
+                    ),
+                };
+                Row::new(vec![
+                    Cell::from(if state.options.is_on(toggle) {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    }),
+                    Cell::from(toggle.label().to_string()),
+                    Cell::from(if active { "active" } else { "" }),
+                    Cell::from(enables.join(", ")).style(self.styles.subtitle_style),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(20),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(vec!["", "Feature", "", "Enables"]).style(self.styles.subtitle_style))
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!("Features of {}", root.name),
+                    self.styles.help_style,
+                ))
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("Space", self.styles.hotkey_style),
+                        Span::styled(": Toggle and resolve──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_workspace(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let height = (state.workspace.len() as u16 + 3).min(area.height.saturating_sub(4));
+        let center_area = popup_area(area, Constraint::Length(80), Constraint::Length(height));
+
+        let root_index = state.selected_package.first().copied();
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = state
+            .workspace
+            .iter()
+            .enumerate()
+            .skip((self.workspace_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(index, member)| {
+                let style = if index == self.workspace_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                Row::new(vec![
+                    Cell::from(if root_index == Some(member.index) {
+                        "✓"
+                    } else {
+                        ""
+                    }),
+                    Cell::from(member.name.clone()),
+                    Cell::from(member.version.clone()),
+                    Cell::from(member.direct_count.to_string()),
+                    Cell::from(member.transitive_count.to_string()),
+                    Cell::from(get_size(member.transitive_size)),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(vec!["", "Member", "Version", "Direct", "All", "Size"])
+                .style(self.styles.subtitle_style),
+        )
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    "Workspace members",
+                    self.styles.help_style,
+                ))
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Use as root──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_duplicates(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let center_area = popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+
+        // every crate takes a line, followed by one line per version
+        let mut rows = Vec::new();
+        let mut selected_line = 0;
+        for (index, duplicate) in state.duplicates.iter().enumerate() {
+            let style = if index == self.duplicates_index {
+                selected_line = rows.len() + duplicate.versions.len();
+                self.styles.selected_style
+            } else {
+                self.styles.text_style
+            };
+            rows.push(
+                Row::new(vec![
+                    Cell::from(duplicate.name.clone()).style(self.styles.duplicate_style),
+                    Cell::from(format!("{} versions", duplicate.versions.len())),
+                    Cell::from(get_size(duplicate.wasted)),
+                    Cell::from(""),
+                ])
+                .style(style),
+            );
+            for version in &duplicate.versions {
+                rows.push(
+                    Row::new(vec![
+                        Cell::from(""),
+                        Cell::from(version.version.clone()),
+                        Cell::from(version.size.map(get_size).unwrap_or_default()),
+                        Cell::from(version.parents.join(", ")),
+                    ])
+                    .style(self.styles.subtitle_style),
+                );
+            }
+        }
+
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = rows
+            .into_iter()
+            .skip((selected_line + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(24),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["Crate", "Version", "Size", "Pulled in by"])
+                .style(self.styles.subtitle_style),
+        )
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!("Duplicated crates: {}", state.duplicates.len()),
+                    self.styles.help_style,
+                ))
+                .title(
+                    Line::styled(
+                        format!(
+                            "Wasted: {}",
+                            get_size(
+                                state
+                                    .duplicates
+                                    .iter()
+                                    .map(|duplicate| duplicate.wasted)
+                                    .sum()
+                            )
+                        ),
+                        self.styles.help_style,
+                    )
+                    .right_aligned(),
+                )
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Show in table──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_license_issues(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let center_area = popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = state
+            .flagged
+            .iter()
+            .enumerate()
+            .skip((self.license_issues_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(position, index)| {
+                let metadata = &state.graph[*index];
+                let style = if position == self.license_issues_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                Row::new(vec![
+                    Cell::from(metadata.name.clone()).style(
+                        self.to_flag_style(state, *index)
+                            .unwrap_or(self.styles.text_style),
+                    ),
+                    Cell::from(metadata.version.clone()),
+                    Cell::from(self.to_license_span(metadata)),
+                    Cell::from(
+                        state
+                            .license_issue(*index)
+                            .map(|issue| issue.reason().to_string())
+                            .unwrap_or_default(),
+                    ),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+        let violations = state
+            .flagged
+            .iter()
+            .filter(|index| {
+                matches!(
+                    state.license_issue(**index),
+                    Some(LicenseIssue::Violation(_))
+                )
+            })
+            .count();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(24),
+                Constraint::Length(12),
+                Constraint::Length(30),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["Crate", "Version", "License", "Reason"])
+                .style(self.styles.subtitle_style),
+        )
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!(
+                        "License violations: {}, to review: {}",
+                        violations,
+                        state.flagged.len() - violations
+                    ),
+                    self.styles.help_style,
+                ))
+                .title(
+                    Line::styled(
+                        if state.license_policy.is_some() {
+                            "Policy loaded"
+                        } else {
+                            "No license policy"
+                        },
+                        self.styles.help_style,
+                    )
+                    .right_aligned(),
+                )
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Show in table──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_advisories(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let center_area = popup_area(area, Constraint::Percentage(90), Constraint::Percentage(80));
+
+        let Some(db) = &state.advisory_db else {
+            let message = Paragraph::new("No advisory database found, clone https://github.com/rustsec/advisory-db and pass it with --advisory-db.")
+                .style(self.styles.text_style)
+                .block(
+                    Block::bordered()
+                        .title(style::Styled::set_style("Advisories", self.styles.help_style))
+                        .title_bottom(
+                            Line::from(vec![
+                                Span::styled("Esc", self.styles.hotkey_style),
+                                Span::styled(": Close", self.styles.text_style),
+                            ])
+                            .right_aligned(),
+                        ),
+                );
+            render_popup(center_area, buf, message);
+            return;
+        };
+
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = state
+            .findings
+            .iter()
+            .enumerate()
+            .skip((self.advisories_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(position, finding)| {
+                let advisory = &db.advisories[finding.advisory];
+                let metadata = &state.graph[finding.index];
+                let style = if position == self.advisories_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                Row::new(vec![
+                    Cell::from(advisory.id.clone()),
+                    Cell::from(format!("{} {}", metadata.name, metadata.version)),
+                    Cell::from(advisory.label()).style(self.to_advisory_style(advisory)),
+                    Cell::from(advisory.title.clone()),
+                    Cell::from(if advisory.patched.is_empty() {
+                        "none".to_string()
+                    } else {
+                        advisory.patched.join(" | ")
+                    }),
+                    Cell::from(if finding.path.is_empty() {
+                        "not below the root".to_string()
+                    } else {
+                        finding
+                            .path
+                            .iter()
+                            .map(|index| state.graph[*index].name.as_str())
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    })
+                    .style(self.styles.subtitle_style),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(18),
+                Constraint::Length(24),
+                Constraint::Length(12),
+                Constraint::Fill(2),
+                Constraint::Length(16),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["ID", "Crate", "Severity", "Title", "Patched", "Path"])
+                .style(self.styles.subtitle_style),
+        )
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!("Advisories: {}", state.findings.len()),
+                    self.styles.help_style,
+                ))
+                .title(
+                    Line::styled(
+                        format!("{} in the database", db.advisories.len()),
+                        self.styles.help_style,
+                    )
+                    .right_aligned(),
+                )
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Show in table──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_removal(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let Some(removal) = &state.removal else {
+            return;
+        };
+        let center_area = popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+
+        let lines: Vec<Line> = removal
+            .diff
+            .iter()
+            .map(|line| match line {
+                DiffLine::Unchanged(text) => {
+                    Line::styled(format!("  {}", text), self.styles.text_style)
+                }
+                DiffLine::Removed(text) => {
+                    Line::styled(format!("- {}", text), self.styles.warning_style)
+                }
+                DiffLine::Added(text) => {
+                    Line::styled(format!("+ {}", text), self.styles.safe_style)
+                }
+                DiffLine::Skipped(count) => Line::styled(
+                    format!("  ⋯ {} unchanged lines", count),
+                    self.styles.subtitle_style,
+                ),
+            })
+            .collect();
+
+        let popup = Paragraph::new(lines)
+            .scroll((self.removal_scroll, 0))
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        format!("Remove {} from {}", removal.name, removal.member),
+                        self.styles.help_style,
+                    ))
+                    .title(
+                        Line::styled(
+                            format!("{}: {}", removal.manifest_path, removal.tables.join(", ")),
+                            self.styles.help_style,
+                        )
+                        .right_aligned(),
+                    )
+                    .title_bottom(
+                        Line::from(vec![
+                            Span::styled("↵", self.styles.hotkey_style),
+                            Span::styled(": Write and resolve again──", self.styles.text_style),
+                            Span::styled("Esc", self.styles.hotkey_style),
+                            Span::styled(": Cancel", self.styles.text_style),
+                        ])
+                        .right_aligned(),
+                    ),
+            );
+
+        render_popup(center_area, buf, popup);
+    }
+
+    fn render_licenses(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let center_area = popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+
+        let total: usize = state.license_groups.iter().map(|group| group.count).sum();
+        let visible_rows = center_area.height.saturating_sub(3) as usize;
+        let rows = state
+            .license_groups
+            .iter()
+            .enumerate()
+            .skip((self.licenses_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(index, group)| {
+                let style = if index == self.licenses_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                let percentage = get_percentage(group.count as u64, total as u64);
+                Row::new(vec![
+                    Cell::from(group.license.clone()),
+                    Cell::from(group.count.to_string()),
+                    Cell::from(get_size(group.size)),
+                    Cell::from(format!("{:>7.2}%", percentage.0)),
+                    Cell::from(percentage.1).style(self.styles.bar_chart_style),
+                    Cell::from(group.names.join(", ")).style(self.styles.subtitle_style),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let popup_table = Table::new(
+            rows,
+            [
+                Constraint::Length(30),
+                Constraint::Length(6),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["License", "Crates", "Size", "Share", "", "Crates"])
+                .style(self.styles.subtitle_style),
+        )
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!(
+                        "Licenses of {} {}: {}",
+                        if state.is_direct { "direct" } else { "all" },
+                        if state.is_reverse {
+                            "dependents"
+                        } else {
+                            "dependencies"
+                        },
+                        state.license_groups.len()
+                    ),
+                    self.styles.help_style,
+                ))
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Show in table──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        render_popup(center_area, buf, popup_table);
+    }
+
+    fn render_paths(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
+        let center_area = popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+
+        let selected_path = state
+            .paths
+            .get(self.paths_index)
+            .cloned()
+            .unwrap_or_default();
+        let [list_area, steps_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(selected_path.len() as u16 + 2),
+        ])
+        .areas(center_area);
+
+        let visible_rows = list_area.height.saturating_sub(2) as usize;
+        let lines = state
+            .paths
+            .iter()
+            .enumerate()
+            .skip((self.paths_index + 1).saturating_sub(visible_rows))
+            .take(visible_rows)
+            .map(|(index, path)| {
+                let style = if index == self.paths_index {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                let names: Vec<String> = path
+                    .iter()
+                    .map(|step| match step.kinds.as_slice() {
+                        [] | [DependencyKind::Normal] => state.graph[step.index].name.clone(),
+                        kinds => format!("{} ({})", state.graph[step.index].name, get_kinds(kinds)),
+                    })
+                    .collect();
+                Line::styled(format!("{:>3}. {}", index + 1, names.join(" → ")), style)
+            })
+            .collect::<Vec<_>>();
+
+        let target = state
+            .get_selected_dep()
+            .map_or(String::new(), |dep| dep.name.clone());
+        let paths_list = Paragraph::new(lines).block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    format!("Why is {} here: {} paths", target, state.paths.len()),
+                    self.styles.help_style,
+                ))
+                .title_bottom(
+                    Line::from(vec![
+                        Span::styled("▲▼", self.styles.hotkey_style),
+                        Span::styled(": Path──", self.styles.text_style),
+                        Span::styled("◄►", self.styles.hotkey_style),
+                        Span::styled(": Package──", self.styles.text_style),
+                        Span::styled("↵", self.styles.hotkey_style),
+                        Span::styled(": Jump──", self.styles.text_style),
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ])
+                    .right_aligned(),
+                ),
+        );
+
+        let rows = selected_path
+            .iter()
+            .enumerate()
+            .map(|(position, step)| {
+                let style = if position == self.path_step {
+                    self.styles.selected_style
+                } else {
+                    self.styles.text_style
+                };
+                let metadata = &state.graph[step.index];
+                Row::new(vec![
+                    Cell::from(format!(
+                        "{}{}",
+                        "  ".repeat(position.min(10)),
+                        metadata.name
+                    )),
+                    Cell::from(metadata.version.clone()),
+                    Cell::from(get_kinds(&step.kinds)),
+                    Cell::from(step.features.join(", ")).style(self.styles.subtitle_style),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+        let steps_table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Fill(1),
+            ],
+        )
+        .column_spacing(1)
+        .block(Block::bordered().title(Line::styled("Kinds and features", self.styles.help_style)));
+
+        render_popup(list_area, buf, paths_list);
+        render_popup(steps_area, buf, steps_table);
+    }
+
+    /// Shown instead of the tables until the graph is resolved.
+    pub fn render_loading(&mut self, area: Rect, buf: &mut Buffer, progress: LoadProgress) {
+        let text = Text::from(vec![
+            Line::styled(progress.label(), self.styles.text_style),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Esc", self.styles.hotkey_style),
+                Span::styled(": Cancel──", self.styles.text_style),
+                Span::styled("Q", self.styles.hotkey_style),
+                Span::styled(": Quit", self.styles.text_style),
+            ]),
+        ]);
+        let center_area = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+        ])
+        .split(area)[1];
+        Widget::render(
+            Paragraph::new(text).alignment(Alignment::Center),
+            center_area,
+            buf,
+        );
+    }
+
     fn render_main(&mut self, area: Rect, buf: &mut Buffer, state: &DataState) {
         // Create layout with header and tables
         let vertical = Layout::vertical([
-            Constraint::Length(5), // Header height
-            Constraint::Length(3), // Filter height
+            Constraint::Length(7), // Header height
+            Constraint::Length(4), // Filter height
             Constraint::Fill(1),   // Tables area
         ]);
 
@@ -126,11 +991,11 @@ impl Screen {
         Widget::render(self.to_stats_table(state), stat_area, buf);
 
         let [left_table, right_table] =
-            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+            Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)])
                 .areas(table_area);
 
         let [sub_description_area, filter_area] =
-            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+            Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)])
                 .areas(filter_area);
 
         self.render_description(buf, state, sub_description_area);
@@ -166,15 +1031,38 @@ impl Screen {
                 if state.is_direct { "Direct" } else { "All" },
                 Style::new().fg(Color::LightRed),
             ),
-            Span::from(" dependencies of "),
+            Span::from(if state.is_reverse {
+                " dependents of "
+            } else {
+                " dependencies of "
+            }),
             Span::from(
                 state
-                    .selected_package
-                    .last()
+                    .current()
                     .map_or("".to_string(), |dep| dep.name.to_string()),
             ),
         ]);
-        let total_size = state.get_filter_deps().iter().map(|dep| dep.size).sum();
+        let total_size = match state.share {
+            Share::Size => state
+                .get_filter_deps()
+                .iter()
+                .filter_map(|dep| state.size_mode.bytes(dep))
+                .sum(),
+            // everything below the root
+            Share::Exclusive => state
+                .selected_package
+                .first()
+                .and_then(|root| {
+                    Some(
+                        state.exclusive(*root)?.size
+                            - state
+                                .size_mode
+                                .bytes(&state.graph[*root])
+                                .unwrap_or_default(),
+                    )
+                })
+                .unwrap_or_default(),
+        };
         let level1_table = Table::new(
             state
                 .get_filter_deps()
@@ -189,19 +1077,81 @@ impl Screen {
                         self.styles.unselected_style
                     };
 
-                    let text_style = if !metadata.documentation.is_empty() {
+                    let text_style = if let Some(style) = self.to_flag_style(state, metadata.index)
+                    {
+                        style
+                    } else if !metadata.documentation.is_empty() {
                         self.styles.link_style
                     } else {
                         self.styles.text_style
                     };
-                    let percentage = get_percentage(metadata.size, total_size);
+                    let exclusive = state.exclusive(metadata.index);
+                    let cumulative = state.cumulative(metadata.index);
+                    let size = match state.share {
+                        Share::Size => state.size_mode.bytes(metadata),
+                        Share::Exclusive => exclusive.map(|exclusive| exclusive.size),
+                    };
+                    let percentage = size
+                        .map(|size| get_percentage(size, total_size))
+                        .unwrap_or_default();
                     Row::new(vec![
                         Cell::from((index + 1).to_string()).style(row_style),
-                        Cell::from(Text::from(metadata.name.clone()).style(text_style))
+                        Cell::from(self.to_name_line(state, metadata.index, text_style))
                             .style(row_style),
                         Cell::from(metadata.version.clone()).style(row_style),
-                        Cell::from(get_size(metadata.size)).style(row_style),
-                        Cell::from(format!("{:>7.2}%", percentage.0)).style(row_style),
+                        self.to_update_cell(metadata, |updates| updates.compatible.as_ref())
+                            .style(row_style),
+                        self.to_update_cell(metadata, |updates| updates.latest.as_ref())
+                            .style(row_style),
+                        Cell::from(get_kinds(metadata.kinds)).style(row_style),
+                        Cell::from(metadata.platforms.join(" | ")).style(row_style),
+                        Cell::from(get_shown_size(&state.size_mode, metadata)).style(row_style),
+                        Cell::from(metadata.size.unpacked.map(get_size).unwrap_or_default())
+                            .style(row_style),
+                        Cell::from(
+                            metadata
+                                .timing
+                                .map(|timing| get_duration(timing.total))
+                                .unwrap_or_default(),
+                        )
+                        .style(row_style),
+                        Cell::from(
+                            metadata
+                                .code
+                                .map(|code| code.total().files.to_string())
+                                .unwrap_or_default(),
+                        )
+                        .style(row_style),
+                        Cell::from(
+                            metadata
+                                .code
+                                .map(|code| get_count(code.total().code))
+                                .unwrap_or_default(),
+                        )
+                        .style(row_style),
+                        self.to_unsafe_cell(state, metadata.index).style(row_style),
+                        Cell::from(state.graph.dependent_count(metadata.index).to_string())
+                            .style(row_style),
+                        Cell::from(
+                            exclusive
+                                .map(|exclusive| {
+                                    format!("{} ({})", get_size(exclusive.size), exclusive.count)
+                                })
+                                .unwrap_or_default(),
+                        )
+                        .style(row_style),
+                        Cell::from(format!(
+                            "{} ({})",
+                            get_size(cumulative.size),
+                            cumulative.count
+                        ))
+                        .style(row_style),
+                        Cell::from(if size.is_some() {
+                            format!("{:>7.2}%", percentage.0)
+                        } else {
+                            String::new()
+                        })
+                        .style(row_style),
                         Cell::from(percentage.1.clone())
                             .style(self.styles.bar_chart_style)
                             .style(row_style),
@@ -209,20 +1159,47 @@ impl Screen {
                 })
                 .collect::<Vec<_>>(),
             vec![
-                Constraint::Percentage(10),
-                Constraint::Percentage(30),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
+                Constraint::Length(5),
+                Constraint::Min(16),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(11),
+                Constraint::Length(10),
             ],
         )
         .header(Row::new(vec![
             "Index",
             "Name",
             "Version",
+            "Update",
+            "Latest",
+            "Kind",
+            "Platform",
             "Size",
-            "Percentage",
+            "Unpacked",
+            "Build",
+            "Files",
+            "Code",
+            "Unsafe",
+            "Used by",
+            "Exclusive",
+            "Cumulative",
+            match state.share {
+                Share::Size => "Percentage",
+                Share::Exclusive => "Exclusive %",
+            },
             "",
         ]))
         .block(
@@ -247,31 +1224,57 @@ impl Screen {
                 if state.is_direct { "Direct" } else { "All" },
                 Style::new().fg(Color::LightRed),
             ),
-            Span::from(" dependencies of "),
-            Span::from(state.get_selected_dep().name),
+            Span::from(if state.is_reverse {
+                " dependents of "
+            } else {
+                " dependencies of "
+            }),
+            Span::from(
+                state
+                    .get_selected_dep()
+                    .map_or("".to_string(), |dep| dep.name.to_string()),
+            ),
+            Span::from(state.get_selected_dep().map_or("".to_string(), |dep| {
+                let cumulative = state.cumulative(dep.index);
+                format!(
+                    " ({} in {} crates)",
+                    get_size(cumulative.size),
+                    cumulative.count
+                )
+            })),
         ]);
         let level2_table = Table::new(
             state
                 .level2_deps
                 .iter()
-                .enumerate()
                 .skip(self.viewport_start)
                 .take(visible_rows as usize)
-                .map(|(index, dep)| {
+                .map(|listed| {
+                    let dep = state.view(listed);
                     Row::new(vec![
-                        Cell::from(dep.name.clone()),
+                        Cell::from(self.to_name_line(
+                            state,
+                            dep.index,
+                            self.to_flag_style(state, dep.index).unwrap_or_default(),
+                        )),
                         Cell::from(dep.version.clone()),
-                        Cell::from(get_size(dep.size)),
+                        Cell::from(get_kinds(dep.kinds)),
+                        Cell::from(dep.platforms.join(" | ")),
+                        Cell::from(get_shown_size(&state.size_mode, &dep)),
                     ])
                 })
                 .collect::<Vec<_>>(),
             vec![
-                Constraint::Percentage(40),
-                Constraint::Percentage(30),
                 Constraint::Percentage(30),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(25),
+                Constraint::Percentage(15),
             ],
         )
-        .header(Row::new(vec!["Name", "Version", "Size"]))
+        .header(Row::new(vec![
+            "Name", "Version", "Kind", "Platform", "Size",
+        ]))
         .style(self.styles.subtitle_style)
         .block(
             Block::default()
@@ -290,14 +1293,76 @@ impl Screen {
         state: &DataState,
         sub_description_area: Rect,
     ) {
-        let data = state.get_selected_dep();
-        let title = Line::from(vec![
-            Span::from("Description of "),
-            Span::from(state.get_selected_dep().name),
-        ]);
-        let sub_description_text = Paragraph::new(data.description)
-            .style(self.styles.subtitle_style)
-            .block(Block::default().borders(Borders::ALL).title(title));
+        let data = state
+            .get_selected_dep()
+            .map(|dep| dep.metadata)
+            .cloned()
+            .unwrap_or_default();
+        let title = Line::from(vec![Span::from("Description of "), Span::from(data.name)]);
+        let breakdown = data
+            .code
+            .map(|code| get_code_breakdown(&code))
+            .unwrap_or_default();
+        let unsafety = Line::styled(
+            data.code
+                .map(|code| get_unsafe_breakdown(&code.unsafety))
+                .unwrap_or_default(),
+            self.styles.text_style,
+        )
+        .right_aligned();
+        let advisories = state
+            .get_selected_dep()
+            .map(|dep| state.advisories(dep.index))
+            .unwrap_or_default();
+        let advisory = match advisories.first() {
+            Some(advisory) => Line::styled(
+                format!("{} {}: {}", advisory.id, advisory.label(), advisory.title),
+                self.to_advisory_style(advisory),
+            )
+            .right_aligned(),
+            None => Line::default(),
+        };
+        let yanked = match state
+            .get_selected_dep()
+            .and_then(|dep| state.yanked(dep.index))
+        {
+            Some(Some(replacement)) => Line::styled(
+                format!("v{} is yanked, use v{}", data.version, replacement),
+                self.styles.advisory_style,
+            ),
+            Some(None) => Line::styled(
+                format!("v{} is yanked", data.version),
+                self.styles.advisory_style,
+            ),
+            None => Line::default(),
+        }
+        .right_aligned();
+        let license = match state
+            .get_selected_dep()
+            .and_then(|dep| state.license_issue(dep.index))
+        {
+            Some(LicenseIssue::Violation(reason)) => {
+                Line::styled(reason.clone(), self.styles.warning_style)
+            }
+            Some(LicenseIssue::Review(reason)) => {
+                Line::styled(reason.clone(), self.styles.review_style)
+            }
+            None => Line::default(),
+        };
+        let sub_description_text = Paragraph::new(vec![
+            Line::from(data.description),
+            Line::styled(breakdown, self.styles.text_style),
+        ])
+        .style(self.styles.subtitle_style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title(yanked)
+                .title(advisory)
+                .title_bottom(license)
+                .title_bottom(unsafety),
+        );
         Widget::render(sub_description_text, sub_description_area, buf);
     }
 
@@ -313,6 +1378,15 @@ impl Screen {
                 Cell::from("⏎ or Esc").style(self.styles.help_style),
                 Cell::from("Exit the filter text box").style(self.styles.text_style),
             ]),
+            Row::new(vec![
+                Cell::from(LICENSE_FILTER).style(self.styles.help_style),
+                Cell::from("Filter by a license of the license summary.")
+                    .style(self.styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from(NAME_FILTER).style(self.styles.help_style),
+                Cell::from("Filter by the whole crate name.").style(self.styles.text_style),
+            ]),
         ];
 
         let help_table = Table::new(help_rows, help_widths).column_spacing(1).block(
@@ -384,22 +1458,37 @@ impl Screen {
             .to_string()
     }
 
-    pub fn to_stats_table(&self, state: &DataState) -> Table {
+    pub fn to_stats_table(&self, state: &DataState) -> Table<'_> {
         let stats_widths = [
             Constraint::Length(20),
             Constraint::Length(20),
             Constraint::Length(20),
             Constraint::Min(40),
         ];
-        let total_size: u64 = state.get_filter_deps().iter().map(|dep| dep.size).sum();
+        let total_size: u64 = state
+            .get_filter_deps()
+            .iter()
+            .filter_map(|dep| state.size_mode.bytes(dep))
+            .sum();
+        let unknown_count = state
+            .get_filter_deps()
+            .iter()
+            .filter(|dep| state.size_mode.bytes(dep).is_none())
+            .count();
+        let yanked_count = state
+            .get_filter_deps()
+            .iter()
+            .filter(|dep| state.yanked(dep.index).is_some())
+            .count();
         let paths: Vec<String> = state
             .selected_package
             .iter()
-            .map(|dep| dep.name.clone())
+            .map(|index| state.graph[*index].name.clone())
             .collect();
-        let joined_path = paths.join("/");
+        // the dependents are walked upwards
+        let joined_path = paths.join(if state.is_reverse { " ← " } else { "/" });
 
-        if let Some(current_crate) = state.selected_package.last() {
+        if let Some(current_crate) = state.current() {
             // Stats Area
             let stats_rows = [
                 Row::new(vec![
@@ -411,35 +1500,286 @@ impl Screen {
                 ]),
                 Row::new(vec![
                     Cell::from("License:").style(self.styles.text_style),
-                    Cell::from(format!("{:5}", current_crate.license))
-                        .style(self.styles.text_style),
+                    Cell::from(self.to_license_span(current_crate)),
                     Cell::from("Description:").style(self.styles.text_style),
                     Cell::from(format!("{:5}", current_crate.description))
                         .style(self.styles.text_style),
                 ]),
                 Row::new(vec![
                     Cell::from("Total count:").style(self.styles.text_style),
-                    Cell::from(format!("{:5}", state.get_filter_deps().len().to_string()))
-                        .style(self.styles.text_style),
+                    Cell::from(Line::from(vec![
+                        Span::styled(
+                            state.get_filter_deps().len().to_string(),
+                            self.styles.text_style,
+                        ),
+                        match yanked_count {
+                            0 => Span::default(),
+                            count => Span::styled(
+                                format!(" ({} yanked)", count),
+                                self.styles.advisory_style,
+                            ),
+                        },
+                    ])),
                     Cell::from("Total size:").style(self.styles.text_style),
-                    Cell::from(format!("{:5}", get_size(total_size)))
-                        .style(self.styles.text_style)
+                    Cell::from(if unknown_count > 0 {
+                        format!(
+                            "{:5} ({} {})",
+                            get_size(total_size),
+                            unknown_count,
+                            if state.size_mode == SizeMode::Source {
+                                "unknown"
+                            } else {
+                                "not built"
+                            }
+                        )
+                    } else {
+                        format!("{:5}", get_size(total_size))
+                    })
+                    .style(self.styles.text_style),
                 ]),
+                Row::new(vec![
+                    Cell::from("Build time:").style(self.styles.text_style),
+                    Cell::from(get_build_time(
+                        &state
+                            .get_filter_deps()
+                            .iter()
+                            .map(|dep| dep.timing)
+                            .collect::<Vec<_>>(),
+                    ))
+                    .style(self.styles.text_style),
+                    Cell::from("Frontend/codegen:").style(self.styles.text_style),
+                    Cell::from(get_build_split(
+                        state.get_filter_deps().iter().filter_map(|dep| dep.timing),
+                    ))
+                    .style(self.styles.text_style),
+                ]),
+                Row::new(
+                    std::iter::once(Cell::from("Kinds:").style(self.styles.text_style))
+                        .chain(self.to_kinds_cells(state)),
+                ),
             ];
             return Table::new(stats_rows, stats_widths)
                 .column_spacing(1)
-                .block(Block::default().title(
-                    Line::from(vec![
-                        Span::styled("Statistics at ", self.styles.text_style),
-                        Span::styled(joined_path, self.styles.title_style),
-                    ])
-                ).borders(Borders::ALL));
+                .block(
+                    Block::default()
+                        .title(Line::from(vec![
+                            Span::styled("Statistics at ", self.styles.text_style),
+                            Span::styled(joined_path, self.styles.title_style),
+                        ]))
+                        .title(
+                            match state.options.fixed_source() {
+                                Some(source) => Line::from(vec![
+                                    Span::styled("Read from: ", self.styles.text_style),
+                                    Span::styled(source, self.styles.title_style),
+                                ]),
+                                None => Line::from(vec![
+                                    Span::styled("T", self.styles.hotkey_style),
+                                    Span::styled("arget: ", self.styles.text_style),
+                                    Span::styled(
+                                        state.options.platform.label(),
+                                        self.styles.title_style,
+                                    ),
+                                ]),
+                            }
+                            .right_aligned(),
+                        )
+                        .title_bottom(
+                            Line::from(vec![
+                                Span::styled("B", self.styles.hotkey_style),
+                                Span::styled(": Size of ", self.styles.text_style),
+                                Span::styled(state.size_mode.label(), self.styles.title_style),
+                            ])
+                            .left_aligned(),
+                        )
+                        .title_bottom(self.to_loading_line(state))
+                        .title_bottom(self.to_edit_line(state))
+                        .borders(Borders::ALL),
+                );
         }
 
         Table::new([Row::new([Cell::from("Error")])], stats_widths)
             .column_spacing(1)
             .block(Block::default().title("Statistics").borders(Borders::ALL))
     }
+
+    /// The progress of the background load, empty when nothing is loading.
+    fn to_loading_line(&self, state: &DataState) -> Line<'_> {
+        match state.loading {
+            Some(progress) => Line::from(vec![
+                Span::styled(progress.label(), self.styles.help_style),
+                Span::styled("──", self.styles.text_style),
+                Span::styled("Esc", self.styles.hotkey_style),
+                Span::styled(": Cancel", self.styles.text_style),
+            ])
+            .right_aligned(),
+            None => Line::default(),
+        }
+    }
+
+    /// What the last manifest edit changed, once the graph is resolved again.
+    fn to_edit_line(&self, state: &DataState) -> Line<'_> {
+        let Some(edit) = state
+            .last_edit
+            .as_ref()
+            .filter(|_edit| state.loading.is_none())
+        else {
+            return Line::default();
+        };
+        let removal = &edit.removal;
+        let mut spans = vec![Span::styled(
+            if edit.undone {
+                format!("Restored {} in {}", removal.name, removal.member)
+            } else {
+                format!("Removed {} from {}", removal.name, removal.member)
+            },
+            self.styles.help_style,
+        )];
+        if let Some(after) = edit.after {
+            spans.push(Span::styled(
+                format!(
+                    ": {} → {} crates ({:+}), {} → {}",
+                    edit.before.count,
+                    after.count,
+                    after.count as i64 - edit.before.count as i64,
+                    get_size(edit.before.size),
+                    get_size(after.size)
+                ),
+                self.styles.text_style,
+            ));
+        }
+        if !edit.undone {
+            spans.extend([
+                Span::styled("──", self.styles.text_style),
+                Span::styled("Z", self.styles.hotkey_style),
+                Span::styled(": Undo", self.styles.text_style),
+            ]);
+        }
+        Line::from(spans).right_aligned()
+    }
+
+    /// The style of a crate name that needs attention, license issues before duplicates.
+    fn to_flag_style(&self, state: &DataState, index: usize) -> Option<Style> {
+        match state.license_issue(index) {
+            Some(LicenseIssue::Violation(_)) => Some(self.styles.warning_style),
+            Some(LicenseIssue::Review(_)) => Some(self.styles.review_style),
+            None if state.is_duplicated(index) => Some(self.styles.duplicate_style),
+            None => None,
+        }
+    }
+
+    /// The name of a package, followed by the most urgent advisory affecting it.
+    fn to_name_line(&self, state: &DataState, index: usize, style: Style) -> Line<'_> {
+        let mut spans = vec![Span::styled(state.graph[index].name.clone(), style)];
+        if state.yanked(index).is_some() {
+            spans.push(Span::styled(" [yanked]", self.styles.advisory_style));
+        }
+        if let Some(advisory) = state.advisories(index).first() {
+            spans.push(Span::styled(
+                format!(" [{}]", advisory.label()),
+                self.to_advisory_style(advisory),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn to_advisory_style(&self, advisory: &Advisory) -> Style {
+        match advisory.kind {
+            AdvisoryKind::Vulnerability => self.styles.advisory_style,
+            _ => self.styles.review_style,
+        }
+    }
+
+    /// A newer release picked by `pick`, colored by how far it is from the resolved version.
+    fn to_update_cell(
+        &self,
+        metadata: &Metadata,
+        pick: impl Fn(&Updates) -> Option<&Version>,
+    ) -> Cell<'_> {
+        let Some(updates) = &metadata.updates else {
+            return Cell::from("");
+        };
+        let Some(version) = pick(updates) else {
+            return Cell::from(Span::styled("✓", self.styles.safe_style));
+        };
+        let style = match Version::parse(&metadata.version)
+            .map(|current| Bump::between(&current, version))
+        {
+            Ok(Bump::Patch) => self.styles.safe_style,
+            Ok(Bump::Minor) => self.styles.review_style,
+            Ok(Bump::Major) | Err(_) => self.styles.warning_style,
+        };
+        Cell::from(Span::styled(version.to_string(), style))
+    }
+
+    /// The license of a package, the license file standing in for a missing one.
+    fn to_license_span(&self, metadata: &Metadata) -> Span<'_> {
+        if metadata.license.is_empty() && !metadata.license_file.is_empty() {
+            Span::styled(
+                format!("see {}", metadata.license_file),
+                self.styles.review_style,
+            )
+        } else {
+            Span::styled(metadata.license.clone(), self.styles.text_style)
+        }
+    }
+
+    /// The own and the transitive `unsafe` uses of a package, with a mark for crates using the lint against it.
+    fn to_unsafe_cell(&self, state: &DataState, index: usize) -> Cell<'_> {
+        let Some(unsafety) = state.graph[index].code.map(|code| code.unsafety) else {
+            return Cell::from("");
+        };
+        let total = state.cumulative(index).unsafe_count;
+        let text = format!("{}/{}", unsafety.count(), total);
+        match unsafety.lint {
+            UnsafeLint::Allowed if unsafety.count() > 0 => {
+                Cell::from(text).style(self.styles.warning_style)
+            }
+            UnsafeLint::Allowed => Cell::from(text),
+            UnsafeLint::Denied | UnsafeLint::Forbidden => {
+                Cell::from(format!("✓ {}", text)).style(self.styles.safe_style)
+            }
+        }
+    }
+
+    /// The dependency kind toggles, with the hotkey of each kind and whether it is shown.
+    fn to_kinds_cells(&self, state: &DataState) -> Vec<Cell<'_>> {
+        DependencyKind::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, kind)| {
+                Cell::from(Line::from(vec![
+                    Span::styled((index + 1).to_string(), self.styles.hotkey_style),
+                    Span::styled(
+                        format!(
+                            ": {} {}",
+                            kind.as_str(),
+                            if state.shown_kinds.contains(kind) {
+                                "✓"
+                            } else {
+                                "✗"
+                            }
+                        ),
+                        self.styles.text_style,
+                    ),
+                ]))
+            })
+            .collect()
+    }
+}
+
+/// The centered area of a popup, `width` by `height` within `area`.
+fn popup_area(area: Rect, width: Constraint, height: Constraint) -> Rect {
+    let [_above, middle, _below] =
+        Layout::vertical([Constraint::Fill(1), height, Constraint::Fill(1)]).areas(area);
+    let [_left, center, _right] =
+        Layout::horizontal([Constraint::Fill(1), width, Constraint::Fill(1)]).areas(middle);
+    center
+}
+
+/// Clears the area of a popup and renders `widget` on top, so nothing of the tables shows through.
+fn render_popup(area: Rect, buf: &mut Buffer, widget: impl Widget) {
+    Widget::render(Clear, area, buf);
+    widget.render(area, buf);
 }
 
 fn get_size(size: u64) -> String {
@@ -460,6 +1800,117 @@ fn get_size(size: u64) -> String {
     format!("{} {}", size, unit)
 }
 
+/// Counts above ten thousand are shortened to thousands.
+fn get_count(count: usize) -> String {
+    if count >= 10_000 {
+        format!("{}k", count / 1000)
+    } else {
+        count.to_string()
+    }
+}
+
+/// The files and lines of a package, and how much of its code is library, tests, examples and benches.
+fn get_code_breakdown(code: &CodeStats) -> String {
+    let total = code.total();
+    format!(
+        "{} files: {} code, {} comments, {} blank──lib {}, tests {}, examples {}, benches {}",
+        total.files,
+        total.code,
+        total.comments,
+        total.blank,
+        code.library.code,
+        code.tests.code,
+        code.examples.code,
+        code.benches.code
+    )
+}
+
+/// The kinds of `unsafe` uses of a package and how it restricts them.
+fn get_unsafe_breakdown(unsafety: &Unsafety) -> String {
+    format!(
+        "unsafe: {} blocks, {} fn, {} impl, {} trait{}",
+        unsafety.blocks,
+        unsafety.functions,
+        unsafety.impls,
+        unsafety.traits,
+        match unsafety.lint {
+            UnsafeLint::Allowed => "",
+            UnsafeLint::Denied => ", deny(unsafe_code)",
+            UnsafeLint::Forbidden => ", forbid(unsafe_code)",
+        }
+    )
+}
+
+fn get_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds >= 60.0 {
+        format!(
+            "{}m {:02}s",
+            duration.as_secs() / 60,
+            duration.as_secs() % 60
+        )
+    } else {
+        format!("{:.1}s", seconds)
+    }
+}
+
+/// The combined compile time of the packages, telling how many are missing from the report.
+fn get_build_time(timings: &[Option<Timing>]) -> String {
+    let timed: Vec<Timing> = timings.iter().flatten().copied().collect();
+    let missing = timings.len() - timed.len();
+    match (timed.is_empty(), missing) {
+        (true, _) => "no timings report".to_string(),
+        (false, 0) => get_duration(timed.iter().map(|timing| timing.total).sum()),
+        (false, missing) => format!(
+            "{:5} ({} not timed)",
+            get_duration(timed.iter().map(|timing| timing.total).sum()),
+            missing
+        ),
+    }
+}
+
+/// The combined frontend and codegen time, as far as the report splits them.
+fn get_build_split(timings: impl Iterator<Item = Timing>) -> String {
+    let (frontend, codegen): (Vec<_>, Vec<_>) = timings
+        .filter_map(|timing| Some((timing.frontend?, timing.codegen?)))
+        .unzip();
+    if frontend.is_empty() {
+        return String::new();
+    }
+    format!(
+        "{} / {}",
+        get_duration(frontend.into_iter().sum()),
+        get_duration(codegen.into_iter().sum())
+    )
+}
+
+/// Like [`get_size`], marking estimated sizes with `~` and showing why a size is missing.
+fn get_known_size(size: &Size) -> String {
+    match (size.status, size.bytes()) {
+        (SizeStatus::Measured, Some(bytes)) => get_size(bytes),
+        (SizeStatus::Estimated, Some(bytes)) => format!("~{}", get_size(bytes)),
+        (status, _) => status.as_str().to_string(),
+    }
+}
+
+/// The size column of a package in the current size mode.
+fn get_shown_size(size_mode: &SizeMode, metadata: &Metadata) -> String {
+    match size_mode {
+        SizeMode::Source => get_known_size(&metadata.size),
+        SizeMode::Compiled(_profile) => size_mode
+            .bytes(metadata)
+            .map_or("not built".to_string(), get_size),
+    }
+}
+
+fn get_kinds(kinds: &[DependencyKind]) -> String {
+    kinds
+        .iter()
+        .map(|kind| kind.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Creates a bar chart representation of a percentage using Unicode block characters
 fn create_bar_chart(percentage: f64) -> String {
     let mut bars = String::new();
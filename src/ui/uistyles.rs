@@ -21,6 +21,16 @@ pub struct UiStyles {
     pub bar_chart_style: Style,
     pub unselected_style: Style,
     pub help_style: Style,
+    /// For values that need a closer look, like `unsafe` code.
+    pub warning_style: Style,
+    /// For values that are fine, like crates forbidding `unsafe` code.
+    pub safe_style: Style,
+    /// For names of crates resolved at more than one version.
+    pub duplicate_style: Style,
+    /// For licenses nobody checked yet, like crates only pointing to a license file.
+    pub review_style: Style,
+    /// For crates with known vulnerabilities.
+    pub advisory_style: Style,
 }
 
 impl Default for UiStyles {
@@ -46,6 +56,11 @@ impl Default for UiStyles {
             unselected_style: Style::default(),
             input_style: Style::new().add_modifier(Modifier::ITALIC),
             bar_chart_style: Style::new().fg(Color::Gray),
+            warning_style: Style::new().fg(Color::LightRed),
+            safe_style: Style::new().fg(Color::LightGreen),
+            duplicate_style: Style::new().fg(Color::LightMagenta),
+            review_style: Style::new().fg(Color::Yellow),
+            advisory_style: Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
         }
     }
 }